@@ -0,0 +1,6 @@
+// Filled by the scene before each camera pass, see `CameraUniforms`.
+layout(std140) uniform Camera {
+    highp mat4 u_projection;
+    highp mat4 u_view;
+    highp vec3 u_camera_position;
+};
@@ -1,6 +1,14 @@
-varying highp vec3 v_lighting;
+#version 300 es
+
+precision highp float;
+
+uniform highp vec3 u_color;
+
+in highp vec3 v_lighting;
+
+out vec4 out_color;
 
 void main() {
-    highp vec3 rgb = vec3(0.75, 0.5, 0.75) * v_lighting;
-    gl_FragColor = vec4(rgb.xyz, 1.0);
+    highp vec3 rgb = u_color * v_lighting;
+    out_color = vec4(rgb.xyz, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+// Filled by the scene once per frame, see `FrameUniforms`.
+layout(std140) uniform Frame {
+    highp vec3 u_ambient;
+    highp float u_time;
+    highp vec3 u_light_directions[MAX_LIGHTS];
+    highp vec3 u_light_colors[MAX_LIGHTS];
+    int u_light_count;
+};
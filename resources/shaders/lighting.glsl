@@ -0,0 +1,16 @@
+#include "frame.glsl"
+
+highp vec3 compute_lighting(highp vec3 normal) {
+    highp vec3 lighting = u_ambient;
+
+    for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= u_light_count) {
+            break;
+        }
+
+        highp float directional = max(dot(normal, normalize(u_light_directions[i])), 0.0);
+        lighting += u_light_colors[i] * directional;
+    }
+
+    return lighting;
+}
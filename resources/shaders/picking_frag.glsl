@@ -0,0 +1,11 @@
+#version 300 es
+
+precision highp float;
+
+uniform highp vec4 u_id;
+
+out vec4 out_color;
+
+void main() {
+    out_color = u_id;
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#version 300 es
+
+layout(location = 0) in vec4 a_position;
+
+uniform mat4 u_world;
+
+#include "camera.glsl"
+
+void main(void) {
+    gl_Position = u_projection * u_view * u_world * a_position;
+}
\ No newline at end of file
@@ -1,22 +1,20 @@
-attribute vec4 a_position;
-attribute vec4 a_normal;
+#version 300 es
+
+layout(location = 0) in vec4 a_position;
+layout(location = 1) in vec4 a_normal;
 
-uniform mat4 u_projection;
 uniform mat4 u_world;
-uniform mat4 u_view;
 uniform mat4 u_normal;
 
-varying highp vec3 v_lighting;
+#include "camera.glsl"
+#include "lighting.glsl"
+
+out highp vec3 v_lighting;
 
 void main(void) {
     gl_Position = u_projection * u_view * u_world * a_position;
 
-    highp vec3 ambientLight = vec3(0.3, 0.3, 0.3);
-    highp vec3 directionalLightColor = vec3(1, 0.75, 1);
-    highp vec3 directionalVector = normalize(vec3(0.85, 0.8, 0.75));
-
     highp vec4 transformedNormal = u_normal * a_normal;
 
-    highp float directional = max(dot(transformedNormal.xyz, directionalVector), 0.0);
-    v_lighting = ambientLight + (directionalLightColor * directional);
+    v_lighting = compute_lighting(transformedNormal.xyz);
 }
\ No newline at end of file
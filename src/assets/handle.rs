@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+pub enum AssetState<T> {
+    LOADING,
+    LOADED(Rc<T>),
+    FAILED(String),
+}
+
+/// Shared reference to an asset of `Assets`, filled in once its file is loaded.
+pub struct Handle<T> {
+    path: Rc<str>,
+    state: Rc<RefCell<AssetState<T>>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle {
+            path: self.path.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match &*self.state.borrow() {
+            AssetState::LOADING => "loading",
+            AssetState::LOADED(_) => "loaded",
+            AssetState::FAILED(_) => "failed",
+        };
+
+        f.debug_struct("Handle")
+            .field("path", &self.path)
+            .field("state", &state)
+            .finish()
+    }
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(path: &str) -> Self {
+        Handle {
+            path: Rc::from(path),
+            state: Rc::new(RefCell::new(AssetState::LOADING)),
+        }
+    }
+
+    pub(crate) fn set(&self, state: AssetState<T>) {
+        *self.state.borrow_mut() = state;
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// The asset, `None` while it loads or when it failed.
+    pub fn get(&self) -> Option<Rc<T>> {
+        match &*self.state.borrow() {
+            AssetState::LOADED(asset) => Some(asset.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(*self.state.borrow(), AssetState::LOADING)
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        matches!(*self.state.borrow(), AssetState::LOADED(_))
+    }
+
+    pub fn get_error(&self) -> Option<String> {
+        match &*self.state.borrow() {
+            AssetState::FAILED(error) => Some(error.clone()),
+            _ => None,
+        }
+    }
+}
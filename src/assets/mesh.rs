@@ -0,0 +1,322 @@
+use crate::assets::Asset;
+use crate::render::{Indices, MAX_U16_VERTICES};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Triangle mesh with one normal per vertex, loaded from Wavefront OBJ files.
+///
+/// Indices are kept as u32 so meshes are not limited in size, `Buffers` stores them as u16
+/// when they fit.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+fn parse_floats<const N: usize>(values: &[&str]) -> Result<[f32; N], String> {
+    let mut floats = [0f32; N];
+
+    if values.len() < N {
+        return Err(format!("expected {} values", N));
+    }
+
+    for (float, value) in floats.iter_mut().zip(values) {
+        *float = value.parse().map_err(|_| format!("invalid number {}", value))?;
+    }
+
+    Ok(floats)
+}
+
+/// OBJ indices start at 1, negative ones count back from the last element.
+fn parse_index(value: &str, count: usize) -> Result<usize, String> {
+    let index: i64 = value.parse().map_err(|_| format!("invalid index {}", value))?;
+
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {} out of range", value));
+    }
+
+    Ok(resolved as usize)
+}
+
+impl Mesh {
+    /// Positions, normals and polygon faces of an OBJ file, polygons are split in triangle fans.
+    /// Vertices without normals get the average normal of their faces.
+    pub fn from_obj(source: &str) -> Result<Self, String> {
+        let mut positions: Vec<[f32; 3]> = vec![];
+        let mut normals: Vec<[f32; 3]> = vec![];
+        let mut corners: HashMap<(usize, Option<usize>), u32> = HashMap::new();
+        let mut smooth: Vec<bool> = vec![];
+        let mut mesh = Mesh::default();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let error = |message: String| format!("line {}: {}", line_index + 1, message);
+
+            let mut values = line.split_whitespace();
+
+            match values.next() {
+                Some("v") => positions.push(parse_floats(&values.collect::<Vec<_>>()).map_err(error)?),
+                Some("vn") => normals.push(parse_floats(&values.collect::<Vec<_>>()).map_err(error)?),
+                Some("f") => {
+                    let mut face = vec![];
+
+                    for corner in values {
+                        let mut parts = corner.split('/');
+
+                        let position = parse_index(parts.next().unwrap_or(""), positions.len()).map_err(error)?;
+
+                        let normal = match parts.nth(1) {
+                            Some(normal) if !normal.is_empty() => {
+                                Some(parse_index(normal, normals.len()).map_err(error)?)
+                            }
+                            _ => None,
+                        };
+
+                        let index = match corners.get(&(position, normal)) {
+                            Some(index) => *index,
+                            None => {
+                                let index = u32::try_from(mesh.vertices.len() / 3)
+                                    .map_err(|_| error("too many vertices for u32 indices".to_string()))?;
+
+                                mesh.vertices.extend_from_slice(&positions[position]);
+                                mesh.normals.extend_from_slice(&normal.map_or([0.; 3], |normal| normals[normal]));
+                                smooth.push(normal.is_none());
+
+                                corners.insert((position, normal), index);
+
+                                index
+                            }
+                        };
+
+                        face.push(index);
+                    }
+
+                    if face.len() < 3 {
+                        return Err(error("face with less than 3 vertices".to_string()));
+                    }
+
+                    for i in 1..face.len() - 1 {
+                        mesh.indices.extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        mesh.smooth_normals(&smooth);
+
+        Ok(mesh)
+    }
+
+    pub fn get_vertex_count(&self) -> usize {
+        self.vertices.len() / 3
+    }
+
+    pub fn get_indices(&self) -> Indices<'_> {
+        Indices::U32(&self.indices)
+    }
+
+    /// Whether `Buffers` can store the indices as u16.
+    pub fn fits_u16(&self) -> bool {
+        self.get_vertex_count() <= MAX_U16_VERTICES
+    }
+
+    /// Splits the mesh in parts of at most `max_vertices` vertices, keeping whole triangles.
+    /// Vertices shared by triangles of different parts are duplicated.
+    pub fn split(&self, max_vertices: usize) -> Result<Vec<Mesh>, &'static str> {
+        if max_vertices < 3 {
+            return Err("parts need at least 3 vertices");
+        }
+
+        let mut parts = vec![];
+        let mut part = Mesh::default();
+        let mut remap: HashMap<u32, u32> = HashMap::new();
+
+        for triangle in self.indices.chunks_exact(3) {
+            let added = triangle.iter().filter(|index| !remap.contains_key(index)).count();
+
+            if part.get_vertex_count() + added > max_vertices {
+                parts.push(std::mem::take(&mut part));
+                remap.clear();
+            }
+
+            for &index in triangle {
+                let remapped = match remap.get(&index) {
+                    Some(remapped) => *remapped,
+                    None => {
+                        let remapped = part.get_vertex_count() as u32;
+                        let i = index as usize * 3;
+
+                        part.vertices.extend_from_slice(&self.vertices[i..i + 3]);
+                        part.normals.extend_from_slice(&self.normals[i..i + 3]);
+
+                        remap.insert(index, remapped);
+
+                        remapped
+                    }
+                };
+
+                part.indices.push(remapped);
+            }
+        }
+
+        if !part.indices.is_empty() {
+            parts.push(part);
+        }
+
+        Ok(parts)
+    }
+
+    /// Parts small enough for u16 indices, for paths that cannot draw with u32 ones.
+    /// Parts have at most `MAX_U16_VERTICES` vertices, so no index is the restart index 65535.
+    pub fn split_u16(&self) -> Vec<Mesh> {
+        if self.fits_u16() {
+            return vec![self.clone()];
+        }
+
+        self.split(MAX_U16_VERTICES).unwrap()
+    }
+
+    fn smooth_normals(&mut self, smooth: &[bool]) {
+        if !smooth.contains(&true) {
+            return;
+        }
+
+        let vertex = |index: u32| {
+            let i = index as usize * 3;
+
+            [self.vertices[i], self.vertices[i + 1], self.vertices[i + 2]]
+        };
+
+        let mut sums = vec![0f32; self.normals.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2]));
+
+            let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+
+            // Unnormalized, so larger faces weigh more in the average.
+            let mut normal = [0f32; 3];
+            vec3::cross(&mut normal, &ab, &ac);
+
+            for &index in triangle {
+                for axis in 0..3 {
+                    sums[index as usize * 3 + axis] += normal[axis];
+                }
+            }
+        }
+
+        for (index, _) in smooth.iter().enumerate().filter(|(_, smooth)| **smooth) {
+            let sum = [sums[index * 3], sums[index * 3 + 1], sums[index * 3 + 2]];
+
+            let mut normal = [0f32; 3];
+            vec3::norm(&mut normal, &sum);
+
+            self.normals[index * 3..index * 3 + 3].copy_from_slice(&normal);
+        }
+    }
+}
+
+impl Asset for Mesh {
+    fn from_bytes(path: &str, bytes: Vec<u8>) -> Result<Self, String> {
+        let source = String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8", path))?;
+
+        Mesh::from_obj(&source).map_err(|error| format!("{}: {}", path, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strip of `count` triangles, every triangle sharing two vertices with the previous one.
+    fn strip(count: u32) -> Mesh {
+        let mut mesh = Mesh::default();
+
+        for i in 0..count + 2 {
+            mesh.vertices.extend_from_slice(&[i as f32, (i % 2) as f32, 0.]);
+            mesh.normals.extend_from_slice(&[0., 0., 1.]);
+        }
+
+        for i in 0..count {
+            mesh.indices.extend_from_slice(&[i, i + 1, i + 2]);
+        }
+
+        mesh
+    }
+
+    fn triangles(mesh: &Mesh) -> Vec<[f32; 9]> {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let mut positions = [0f32; 9];
+
+                for (corner, &index) in triangle.iter().enumerate() {
+                    let i = index as usize * 3;
+
+                    positions[corner * 3..corner * 3 + 3].copy_from_slice(&mesh.vertices[i..i + 3]);
+                }
+
+                positions
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_keeps_triangles_in_order() {
+        let mesh = strip(10);
+        let parts = mesh.split(5).unwrap();
+
+        assert!(parts.iter().all(|part| part.get_vertex_count() <= 5));
+        assert!(parts.iter().all(|part| part.normals.len() == part.vertices.len()));
+
+        let split: Vec<_> = parts.iter().flat_map(triangles).collect();
+
+        assert_eq!(split, triangles(&mesh));
+    }
+
+    #[test]
+    fn split_duplicates_vertices_shared_across_parts() {
+        let parts = strip(4).split(4).unwrap();
+
+        // Two triangles of the strip use 4 vertices, the second part starts over with the shared edge.
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].indices, vec![0, 1, 2, 1, 2, 3]);
+        assert_eq!(parts[1].indices, vec![0, 1, 2, 1, 2, 3]);
+        assert_eq!(parts[1].vertices[..3], [2., 0., 0.]);
+    }
+
+    #[test]
+    fn split_rejects_parts_smaller_than_a_triangle() {
+        assert!(strip(1).split(2).is_err());
+        assert_eq!(strip(1).split(3).unwrap(), vec![strip(1)]);
+    }
+
+    #[test]
+    fn split_u16_keeps_indices_below_the_restart_index() {
+        let small = strip(MAX_U16_VERTICES as u32 - 2);
+
+        assert!(small.fits_u16());
+        assert_eq!(small.split_u16(), vec![small.clone()]);
+
+        let mesh = strip(MAX_U16_VERTICES as u32 + 100);
+
+        assert!(!mesh.fits_u16());
+
+        let parts = mesh.split_u16();
+
+        assert_eq!(parts.len(), 2);
+
+        for part in parts.iter() {
+            assert!(part.fits_u16());
+            assert!(part.indices.iter().all(|&index| index < u16::MAX as u32));
+        }
+
+        let split: Vec<_> = parts.iter().flat_map(triangles).collect();
+
+        assert_eq!(split, triangles(&mesh));
+    }
+}
@@ -0,0 +1,356 @@
+mod handle;
+mod mesh;
+mod source;
+mod texture;
+
+pub use handle::*;
+pub use mesh::*;
+pub use source::*;
+pub use texture::*;
+
+use crate::component::Component;
+use crate::engine::SceneDescription;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// Content `Assets` can build from the bytes of a file.
+pub trait Asset: Sized + 'static {
+    fn from_bytes(path: &str, bytes: Vec<u8>) -> Result<Self, String>;
+}
+
+impl Asset for Vec<u8> {
+    fn from_bytes(_path: &str, bytes: Vec<u8>) -> Result<Self, String> {
+        Ok(bytes)
+    }
+}
+
+/// Text files, such as shader sources.
+impl Asset for String {
+    fn from_bytes(path: &str, bytes: Vec<u8>) -> Result<Self, String> {
+        String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8", path))
+    }
+}
+
+/// Scene files, parsed as RON or JSON from their extension.
+impl Asset for SceneDescription {
+    fn from_bytes(path: &str, bytes: Vec<u8>) -> Result<Self, String> {
+        let source = String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8", path))?;
+
+        let description = if path.ends_with(".ron") {
+            SceneDescription::from_ron(&source)
+        } else if path.ends_with(".json") {
+            SceneDescription::from_json(&source)
+        } else {
+            return Err(format!("{} is not a .ron or .json scene", path));
+        };
+
+        description.map_err(|error| format!("{}: {}", path, error))
+    }
+}
+
+type LoadedCallback = Box<dyn FnOnce(&Assets)>;
+
+struct AssetsState {
+    source: Rc<dyn AssetSource>,
+    /// `Handle<T>` of every requested file, by asset type and path.
+    cache: HashMap<(TypeId, String), Box<dyn Any>>,
+    requested: usize,
+    finished: usize,
+    errors: Vec<(String, String)>,
+    on_loaded: Vec<LoadedCallback>,
+}
+
+/// Loads files asynchronously from a source and caches them by path.
+///
+/// Loading the same path twice returns the same handle, and handles are filled in
+/// as files arrive. The default source fetches relative to the page on the web and
+/// reads relative to the working directory natively.
+#[derive(Clone)]
+pub struct Assets {
+    state: Rc<RefCell<AssetsState>>,
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let source = FetchSource::default();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let source = DirectorySource::new(".");
+
+        Assets::new(source)
+    }
+}
+
+impl fmt::Debug for Assets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+
+        f.debug_struct("Assets")
+            .field("source", &state.source)
+            .field("requested", &state.requested)
+            .field("finished", &state.finished)
+            .finish()
+    }
+}
+
+impl Assets {
+    pub fn new(source: impl AssetSource + 'static) -> Self {
+        Assets {
+            state: Rc::new(RefCell::new(AssetsState {
+                source: Rc::new(source),
+                cache: HashMap::new(),
+                requested: 0,
+                finished: 0,
+                errors: vec![],
+                on_loaded: vec![],
+            })),
+        }
+    }
+
+    /// Handle to the asset at `path`, read from the source on the first request only.
+    /// A path that failed to load is read again by the next request.
+    pub fn load<T: Asset>(&self, path: &str) -> Handle<T> {
+        let key = (TypeId::of::<T>(), path.to_string());
+
+        let handle = Handle::<T>::new(path);
+
+        let source = {
+            let mut state = self.state.borrow_mut();
+
+            if let Some(cached) = state.cache.get(&key) {
+                return cached.downcast_ref::<Handle<T>>().unwrap().clone();
+            }
+
+            state.cache.insert(key, Box::new(handle.clone()));
+
+            state.requested += 1;
+
+            state.source.clone()
+        };
+
+        let assets = self.clone();
+        let loading = handle.clone();
+
+        // The state is released, directory sources call back before `read` returns.
+        source.read(
+            path,
+            Box::new(move |bytes| {
+                let path = loading.get_path().to_string();
+
+                match bytes.and_then(|bytes| T::from_bytes(&path, bytes)) {
+                    Ok(asset) => loading.set(AssetState::LOADED(Rc::new(asset))),
+                    Err(error) => {
+                        let mut state = assets.state.borrow_mut();
+
+                        // A later load of the path reads it again instead of returning this failure.
+                        state.cache.remove(&(TypeId::of::<T>(), path.clone()));
+                        state.errors.push((path, error.clone()));
+
+                        drop(state);
+
+                        loading.set(AssetState::FAILED(error));
+                    }
+                }
+
+                assets.finish();
+            }),
+        );
+
+        handle
+    }
+
+    /// Handle of an asset already requested with `load`.
+    pub fn get<T: Asset>(&self, path: &str) -> Option<Handle<T>> {
+        self.state
+            .borrow()
+            .cache
+            .get(&(TypeId::of::<T>(), path.to_string()))
+            .and_then(|handle| handle.downcast_ref::<Handle<T>>())
+            .cloned()
+    }
+
+    fn finish(&self) {
+        let callbacks = {
+            let mut state = self.state.borrow_mut();
+
+            state.finished += 1;
+
+            if state.finished < state.requested {
+                return;
+            }
+
+            std::mem::take(&mut state.on_loaded)
+        };
+
+        for callback in callbacks {
+            callback(self);
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        let state = self.state.borrow();
+
+        state.finished < state.requested
+    }
+
+    /// Fraction of the requested files that finished loading, for loading screens.
+    pub fn get_progress(&self) -> f32 {
+        let state = self.state.borrow();
+
+        if state.requested == 0 {
+            1.
+        } else {
+            state.finished as f32 / state.requested as f32
+        }
+    }
+
+    /// Path and reason of every file that failed to load.
+    pub fn get_errors(&self) -> Vec<(String, String)> {
+        self.state.borrow().errors.clone()
+    }
+
+    /// Calls `callback` once every requested file finished loading, right away if none is pending.
+    pub fn on_loaded(&self, callback: impl FnOnce(&Assets) + 'static) {
+        if self.is_loading() {
+            self.state.borrow_mut().on_loaded.push(Box::new(callback));
+        } else {
+            callback(self);
+        }
+    }
+}
+
+impl Component for Assets {
+    fn update(&mut self, _dt: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Completes reads when the test says so, like a server would.
+    #[derive(Default, Clone)]
+    struct DeferredSource {
+        files: MemorySource,
+        pending: Rc<RefCell<Vec<(String, ReadCallback)>>>,
+    }
+
+    impl fmt::Debug for DeferredSource {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("DeferredSource")
+                .field("pending", &self.pending.borrow().len())
+                .finish()
+        }
+    }
+
+    impl DeferredSource {
+        fn complete(&self) {
+            let pending = std::mem::take(&mut *self.pending.borrow_mut());
+
+            for (path, done) in pending {
+                self.files.read(&path, done);
+            }
+        }
+    }
+
+    impl AssetSource for DeferredSource {
+        fn read(&self, path: &str, done: ReadCallback) {
+            self.pending.borrow_mut().push((path.to_string(), done));
+        }
+    }
+
+    #[test]
+    fn same_path_is_read_once() {
+        let source = MemorySource::default();
+
+        source.set("a.txt", "first");
+
+        let assets = Assets::new(source.clone());
+        let first = assets.load::<String>("a.txt");
+
+        source.set("a.txt", "second");
+
+        let second = assets.load::<String>("a.txt");
+
+        assert_eq!(second.get().as_deref().map(String::as_str), Some("first"));
+        assert_eq!(first.get(), second.get());
+        assert!(assets.get::<String>("a.txt").is_some());
+        // The cache is per asset type.
+        assert!(assets.get::<Vec<u8>>("a.txt").is_none());
+    }
+
+    #[test]
+    fn progress_follows_completed_reads() {
+        let source = DeferredSource::default();
+
+        source.files.set("a.txt", "a");
+        source.files.set("b.txt", "b");
+
+        let assets = Assets::new(source.clone());
+
+        assert_eq!(assets.get_progress(), 1.);
+
+        let a = assets.load::<String>("a.txt");
+        let b = assets.load::<String>("b.txt");
+        assets.load::<String>("a.txt");
+
+        let loaded = Rc::new(RefCell::new(false));
+
+        {
+            let loaded = loaded.clone();
+            assets.on_loaded(move |_| *loaded.borrow_mut() = true);
+        }
+
+        assert!(assets.is_loading());
+        assert!(a.is_loading() && b.is_loading());
+        assert_eq!(assets.get_progress(), 0.);
+        assert!(!*loaded.borrow());
+
+        source.complete();
+
+        assert!(!assets.is_loading());
+        assert_eq!(assets.get_progress(), 1.);
+        assert!(a.is_loaded() && b.is_loaded());
+        assert!(*loaded.borrow());
+    }
+
+    #[test]
+    fn failed_load_is_retried() {
+        let source = MemorySource::default();
+        let assets = Assets::new(source.clone());
+
+        let failed = assets.load::<String>("missing.txt");
+
+        assert!(failed.get_error().is_some());
+        assert_eq!(assets.get_errors().len(), 1);
+        assert!(assets.get::<String>("missing.txt").is_none());
+
+        source.set("missing.txt", "found");
+
+        let retried = assets.load::<String>("missing.txt");
+
+        assert_eq!(retried.get().as_deref().map(String::as_str), Some("found"));
+    }
+
+    #[test]
+    fn directory_source_reads_relative_to_its_root() {
+        let root = std::env::temp_dir().join(format!("assets-test-{}", std::process::id()));
+
+        std::fs::create_dir_all(root.join("shaders")).unwrap();
+        std::fs::write(root.join("shaders/test.glsl"), "void main() {}").unwrap();
+
+        let assets = Assets::new(DirectorySource::new(&root));
+
+        let shader = assets.load::<String>("shaders/test.glsl");
+        let missing = assets.load::<String>("shaders/missing.glsl");
+
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(shader.get().as_deref().map(String::as_str), Some("void main() {}"));
+        assert!(missing.get_error().is_some());
+        assert_eq!(assets.get_progress(), 1.);
+    }
+}
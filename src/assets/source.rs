@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+/// Called once with the content of the file or the reason it could not be read.
+pub type ReadCallback = Box<dyn FnOnce(Result<Vec<u8>, String>)>;
+
+/// Where `Assets` reads files from.
+pub trait AssetSource: Debug {
+    /// Reads `path`, `done` may be called right away or once the read completes.
+    fn read(&self, path: &str, done: ReadCallback);
+}
+
+/// Fetches files over HTTP, relative to `base_url` (the page when empty).
+#[derive(Default, Clone, Debug)]
+pub struct FetchSource {
+    base_url: String,
+    revalidate: bool,
+}
+
+impl FetchSource {
+    pub fn new(base_url: &str) -> Self {
+        FetchSource {
+            base_url: base_url.to_string(),
+            ..FetchSource::default()
+        }
+    }
+
+    /// Asks the server whether files changed instead of reusing the browser cache, for polling.
+    pub fn set_revalidate(mut self, revalidate: bool) -> Self {
+        self.revalidate = revalidate;
+        self
+    }
+
+    fn get_url(&self, path: &str) -> String {
+        if self.base_url.is_empty() || self.base_url.ends_with('/') {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}/{}", self.base_url, path)
+        }
+    }
+}
+
+async fn fetch(url: String, revalidate: bool) -> Result<Vec<u8>, String> {
+    let window = web_sys::window().ok_or("no window to fetch from")?;
+
+    let init = web_sys::RequestInit::new();
+
+    if revalidate {
+        init.set_cache(web_sys::RequestCache::NoCache);
+    }
+
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_str_and_init(&url, &init))
+        .await
+        .map_err(|_| format!("failed to fetch {}", url))?
+        .dyn_into()
+        .map_err(|_| format!("failed to fetch {}", url))?;
+
+    if !response.ok() {
+        return Err(format!("failed to fetch {}: status {}", url, response.status()));
+    }
+
+    let buffer = response.array_buffer().map_err(|_| format!("failed to read {}", url))?;
+
+    let buffer = JsFuture::from(buffer).await.map_err(|_| format!("failed to read {}", url))?;
+
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+impl AssetSource for FetchSource {
+    fn read(&self, path: &str, done: ReadCallback) {
+        let url = self.get_url(path);
+        let revalidate = self.revalidate;
+
+        wasm_bindgen_futures::spawn_local(async move {
+            done(fetch(url, revalidate).await);
+        });
+    }
+}
+
+/// Reads files from a directory, used natively and as a local stand-in for tests.
+#[derive(Clone, Debug)]
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DirectorySource { root: root.into() }
+    }
+}
+
+impl AssetSource for DirectorySource {
+    fn read(&self, path: &str, done: ReadCallback) {
+        let file = self.root.join(path);
+
+        done(std::fs::read(&file).map_err(|error| format!("failed to read {}: {}", file.display(), error)));
+    }
+}
+
+/// Files kept in memory, a local stand-in for a server whose files can be changed from tests.
+#[derive(Default, Clone, Debug)]
+pub struct MemorySource {
+    files: Rc<RefCell<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemorySource {
+    pub fn set(&self, path: &str, bytes: impl Into<Vec<u8>>) {
+        self.files.borrow_mut().insert(path.to_string(), bytes.into());
+    }
+
+    pub fn remove(&self, path: &str) {
+        self.files.borrow_mut().remove(path);
+    }
+}
+
+impl AssetSource for MemorySource {
+    fn read(&self, path: &str, done: ReadCallback) {
+        let bytes = self.files.borrow().get(path).cloned();
+
+        done(bytes.ok_or_else(|| format!("{} does not exist", path)));
+    }
+}
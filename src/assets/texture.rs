@@ -0,0 +1,78 @@
+use crate::assets::Asset;
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+
+/// Encoded image file, decoded by the browser when it is turned into an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    bytes: Vec<u8>,
+    mime: String,
+}
+
+impl Texture {
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn get_mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// Image element for `tex_image_2d`, it can only be uploaded once its `complete` is true.
+    /// Its `onload` and `onerror` release the blob, wait for it with `add_event_listener`.
+    pub fn create_image(&self) -> Result<web_sys::HtmlImageElement, &'static str> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(self.bytes.as_slice()));
+
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type(&self.mime);
+
+        let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+            .map_err(|_| "failed to create blob")?;
+
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|_| "failed to create url")?;
+
+        let image: web_sys::HtmlImageElement = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("img")
+            .map_err(|_| "failed to create image")?
+            .dyn_into()
+            .map_err(|_| "failed to create image")?;
+
+        // The blob is only needed until the image read it, whether it decoded or not.
+        let revoke = {
+            let url = url.clone();
+
+            Closure::once_into_js(move || {
+                web_sys::Url::revoke_object_url(&url).ok();
+            })
+        };
+
+        image.set_onload(Some(revoke.unchecked_ref()));
+        image.set_onerror(Some(revoke.unchecked_ref()));
+
+        image.set_src(&url);
+
+        Ok(image)
+    }
+}
+
+impl Asset for Texture {
+    fn from_bytes(path: &str, bytes: Vec<u8>) -> Result<Self, String> {
+        let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+
+        let mime = match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => return Err(format!("{} is not a supported image", path)),
+        };
+
+        Ok(Texture {
+            bytes,
+            mime: mime.to_string(),
+        })
+    }
+}
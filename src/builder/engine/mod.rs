@@ -1,5 +1,7 @@
 mod scene;
 mod engine;
+mod prefab;
 
 pub use engine::*;
+pub use prefab::*;
 pub use scene::*;
\ No newline at end of file
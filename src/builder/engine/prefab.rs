@@ -0,0 +1,91 @@
+use crate::*;
+
+#[derive (Default)]
+pub struct PrefabBuilder {
+    inner: Prefab
+}
+
+impl PrefabBuilder {
+    pub fn add_object(mut self, object: ObjectDescription) -> Result<Self, &'static str> {
+        self.get_inner_mut().parts.push(PrefabPart::OBJECT(object));
+
+        Ok(self)
+    }
+
+    pub fn add_prefab(mut self, instance: PrefabInstance) -> Result<Self, &'static str> {
+        self.get_inner_mut().parts.push(PrefabPart::PREFAB(instance));
+
+        Ok(self)
+    }
+}
+
+impl Builder<Prefab> for PrefabBuilder {
+    fn get_inner(&self) -> &Prefab {
+        &self.inner
+    }
+
+    fn get_inner_mut(&mut self) -> &mut Prefab {
+        &mut self.inner
+    }
+
+    fn build(self) -> Result<Prefab, &'static str> {
+        Ok(self.inner)
+    }
+}
+
+pub struct InstanceBuilder {
+    inner: PrefabInstance
+}
+
+impl InstanceBuilder {
+    pub fn new(prefab: &str) -> Self {
+        InstanceBuilder {
+            inner: PrefabInstance::new(prefab)
+        }
+    }
+
+    pub fn set_transform(mut self, transform: TransformDescription) -> Result<Self, &'static str> {
+        self.get_inner_mut().overrides.transform = Some(transform);
+
+        Ok(self)
+    }
+
+    pub fn set_material(mut self, material: Material) -> Result<Self, &'static str> {
+        self.get_inner_mut().overrides.material = Some(material);
+
+        Ok(self)
+    }
+
+    pub fn set_layer_mask(mut self, layer_mask: u32) -> Result<Self, &'static str> {
+        self.get_inner_mut().overrides.layer_mask = Some(layer_mask);
+
+        Ok(self)
+    }
+
+    /// Overrides fields of the controllers registered as `controller`.
+    pub fn set_params<P: serde::Serialize>(mut self, controller: &str, params: P) -> Result<Self, &'static str> {
+        let params = serde_json::to_value(params).map_err(|_| "failed to serialize params")?;
+
+        if !params.is_object() {
+            return Err("params must serialize to an object");
+        }
+
+        self.get_inner_mut().overrides.params.insert(controller.to_string(), params);
+
+        Ok(self)
+    }
+}
+
+impl Builder<PrefabInstance> for InstanceBuilder {
+    fn get_inner(&self) -> &PrefabInstance {
+        &self.inner
+    }
+
+    fn get_inner_mut(&mut self) -> &mut PrefabInstance {
+        &mut self.inner
+    }
+
+    fn build(self) -> Result<PrefabInstance, &'static str> {
+        Ok(self.inner)
+    }
+}
\ No newline at end of file
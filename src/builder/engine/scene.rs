@@ -5,6 +5,67 @@ pub struct SceneBuilder {
     inner: Scene
 }
 
+impl SceneBuilder {
+    pub fn set_collision_events(mut self, collision_events: bool) -> Result<Self, &'static str> {
+        self.get_inner_mut().set_collision_events(collision_events);
+
+        Ok(self)
+    }
+
+    pub fn set_lights(mut self, lights: Vec<Light>) -> Result<Self, &'static str> {
+        self.get_inner_mut().set_lights(lights);
+
+        Ok(self)
+    }
+
+    pub fn attach_prefab(mut self, instance: &PrefabInstance, context: &SceneContext) -> Result<Self, String> {
+        for object in context.registry.instantiate(instance, context)? {
+            self.get_inner_mut().push_object(object);
+        }
+
+        Ok(self)
+    }
+
+    /// Adds the objects, prefab instances, cameras and lights of a description, built through the
+    /// registry of `context`. The scene queues its commands on those of `context`.
+    pub fn load(mut self, description: &SceneDescription, context: &SceneContext) -> Result<Self, String> {
+        let registry = &context.registry;
+        let scene = self.get_inner_mut();
+
+        scene.attach_component(context.commands.clone());
+
+        let mut objects = description.objects.clone();
+
+        for (name, prefab) in description.prefabs.iter() {
+            registry.define_prefab(name, prefab.clone());
+        }
+
+        for instance in description.instances.iter() {
+            objects.extend(registry.expand_prefab(instance)?);
+        }
+
+        for object in objects.iter() {
+            let loaded = registry.load_object(object, context)?;
+
+            if object.enabled {
+                scene.push_object(loaded);
+            } else {
+                scene.push_disabled_object(loaded);
+            }
+        }
+
+        for camera in description.cameras.iter() {
+            scene.push_camera(registry.load_camera(camera, context)?);
+        }
+
+        scene.set_lights(description.lights.clone());
+        scene.set_collision_events(description.collision_events);
+        scene.set_fixed_timestep(description.fixed_timestep)?;
+
+        Ok(self)
+    }
+}
+
 impl Builder<Scene> for SceneBuilder {
     fn get_inner(&self) -> &Scene {
         &self.inner
@@ -15,6 +15,14 @@ impl<C> Children<C> {
         self.vector.push(object);
     }
 
+    pub fn len(&self) -> usize {
+        self.vector.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vector.is_empty()
+    }
+
     pub fn get(&self, index: usize) -> &C {
         &self.vector[index]
     }
@@ -1,23 +1,111 @@
 use crate::component::Component;
+use crate::engine::ActionEvent;
 use crate::io::*;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeSet;
+use std::rc::Rc;
 use web_sys::HtmlCanvasElement;
 
+/// Actions held on the current and previous frame.
+#[derive(Default, Debug)]
+struct ActionState {
+    current: BTreeSet<String>,
+    previous: BTreeSet<String>,
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct Input {
     mouse: Mouse,
     keyboard: Keyboard,
-    gamepads: Vec<Gamepad>,
+    gamepads: Gamepads,
+    touch: Touch,
+    map: Rc<RefCell<InputMap>>,
+    actions: Rc<RefCell<ActionState>>,
+    /// Live devices are used while no source is set.
+    source: Rc<RefCell<Option<Box<dyn InputSource>>>>,
 }
 
 impl Input {
     pub fn new(canvas: &HtmlCanvasElement) -> Self {
         Input {
             mouse: Mouse::new(canvas),
-            keyboard: Keyboard::default(),
-            gamepads: vec![],
+            keyboard: Keyboard::new(),
+            gamepads: Gamepads::default(),
+            touch: Touch::new(canvas),
+            map: Rc::new(RefCell::new(InputMap::default())),
+            actions: Rc::new(RefCell::new(ActionState::default())),
+            source: Rc::new(RefCell::new(None)),
         }
     }
 
+    /// Applies the next frame of the source to the devices, done by the engine before any update.
+    /// Returns the dt to simulate, which differs from `dt` when replaying.
+    pub fn latch(&self, dt: f32) -> f32 {
+        let frame = self
+            .source
+            .borrow_mut()
+            .as_mut()
+            .and_then(|source| source.next_frame(self, dt));
+
+        let frame = match frame {
+            Some(frame) => frame,
+            None => {
+                // Exhausted sources hand control back to the live devices.
+                self.source.replace(None);
+
+                DomSource::default().next_frame(self, dt).unwrap()
+            }
+        };
+
+        self.keyboard.apply(&frame.keyboard);
+        self.mouse.apply(&frame.mouse);
+        self.gamepads.apply(&frame.gamepads);
+        self.touch.apply(&frame.touch);
+
+        let current = self
+            .map
+            .borrow()
+            .get_actions()
+            .into_iter()
+            .filter(|action| self.is_action_down(action))
+            .collect();
+
+        let mut actions = self.actions.borrow_mut();
+
+        actions.previous = std::mem::replace(&mut actions.current, current);
+
+        frame.dt
+    }
+
+    /// Replaces where the frames come from for every clone of this input.
+    pub fn set_source(&self, source: Box<dyn InputSource>) {
+        self.source.replace(Some(source));
+    }
+
+    /// Goes back to the live devices.
+    pub fn clear_source(&self) {
+        self.source.replace(None);
+    }
+
+    /// Starts recording every following frame of the current source.
+    pub fn record(&self) -> Recording {
+        let recording = Recording::default();
+
+        let source = self
+            .source
+            .borrow_mut()
+            .take()
+            .unwrap_or_else(|| Box::new(DomSource::default()));
+
+        self.set_source(Box::new(Recorder::new(source, &recording)));
+
+        recording
+    }
+
+    pub fn replay(&self, recording: &Recording) {
+        self.set_source(Box::new(Replay::new(recording)));
+    }
+
     pub fn get_mouse(&self) -> &Mouse {
         &self.mouse
     }
@@ -25,6 +113,70 @@ impl Input {
     pub fn get_keyboard(&self) -> &Keyboard {
         &self.keyboard
     }
+
+    pub fn get_gamepads(&self) -> &Gamepads {
+        &self.gamepads
+    }
+
+    pub fn get_touch(&self) -> &Touch {
+        &self.touch
+    }
+
+    /// Replaces the bindings for every clone of this input.
+    pub fn set_map(&self, map: InputMap) {
+        *self.map.borrow_mut() = map;
+    }
+
+    pub fn get_map(&self) -> Ref<'_, InputMap> {
+        self.map.borrow()
+    }
+
+    pub fn get_map_mut(&self) -> RefMut<'_, InputMap> {
+        self.map.borrow_mut()
+    }
+
+    pub fn is_action_down(&self, action: &str) -> bool {
+        self.map
+            .borrow()
+            .is_action_down(action, &self.keyboard, &self.mouse, &self.gamepads, &self.touch)
+    }
+
+    /// Action went down this frame.
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        let actions = self.actions.borrow();
+
+        actions.current.contains(action) && !actions.previous.contains(action)
+    }
+
+    /// Action went up this frame.
+    pub fn is_action_released(&self, action: &str) -> bool {
+        let actions = self.actions.borrow();
+
+        !actions.current.contains(action) && actions.previous.contains(action)
+    }
+
+    /// Every action pressed or released this frame.
+    pub fn get_action_events(&self) -> Vec<ActionEvent> {
+        let actions = self.actions.borrow();
+
+        let pressed = actions.current.difference(&actions.previous).map(|action| ActionEvent {
+            action: action.clone(),
+            pressed: true,
+        });
+
+        let released = actions.previous.difference(&actions.current).map(|action| ActionEvent {
+            action: action.clone(),
+            pressed: false,
+        });
+
+        pressed.chain(released).collect()
+    }
+
+    pub fn get_axis(&self, axis: &str) -> f32 {
+        self.map
+            .borrow()
+            .get_axis(axis, &self.keyboard, &self.mouse, &self.gamepads, &self.touch)
+    }
 }
 
 impl Component for Input {
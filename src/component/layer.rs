@@ -0,0 +1,35 @@
+use crate::component::Component;
+
+/// Render layers an object belongs to, matched against a camera's layer mask.
+#[derive(Clone, Copy, Debug)]
+pub struct Layer {
+    mask: u32,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer { mask: 1 }
+    }
+}
+
+impl Layer {
+    pub fn new(index: u32) -> Self {
+        Layer { mask: 1 << index }
+    }
+
+    pub fn from_mask(mask: u32) -> Self {
+        Layer { mask }
+    }
+
+    pub fn get_mask(&self) -> u32 {
+        self.mask
+    }
+
+    pub fn set_mask(&mut self, mask: u32) {
+        self.mask = mask;
+    }
+}
+
+impl Component for Layer {
+    fn update(&mut self, _dt: f32) {}
+}
@@ -0,0 +1,59 @@
+use crate::component::Component;
+use crate::render::{get_type_name, ShaderProgram, UniformValue};
+use serde::{Deserialize, Serialize};
+use web_sys::WebGl2RenderingContext;
+
+/// Surface of an object, uploaded as `u_color` before it is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Material {
+    pub color: [f32; 3],
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            color: [0.75, 0.5, 0.75],
+        }
+    }
+}
+
+impl Material {
+    pub fn new(color: [f32; 3]) -> Self {
+        Material { color }
+    }
+
+    /// Uniforms the material sets, by name.
+    pub fn get_uniforms(&self) -> Vec<(&'static str, &dyn UniformValue)> {
+        vec![("u_color", &self.color)]
+    }
+
+    pub fn apply(&self, gl: &WebGl2RenderingContext, program: &ShaderProgram) {
+        for (name, value) in self.get_uniforms() {
+            program.set_uniform(gl, name, value);
+        }
+    }
+
+    /// Warnings for the uniforms of the material `program` does not have or has with another type.
+    pub fn validate(&self, program: &ShaderProgram) -> Vec<String> {
+        let mut warnings = vec![];
+
+        for (name, value) in self.get_uniforms() {
+            match program.get_uniform(name) {
+                Some(uniform) if !value.accepts(uniform.kind) => warnings.push(format!(
+                    "material sets {} but the shader declares it as a {}",
+                    name,
+                    get_type_name(uniform.kind)
+                )),
+                Some(_) => {}
+                None => warnings.push(format!("material sets {} but the shader does not use it", name)),
+            }
+        }
+
+        warnings
+    }
+}
+
+impl Component for Material {
+    fn update(&mut self, _dt: f32) {}
+}
@@ -1,21 +1,64 @@
 mod children;
 mod input;
+mod layer;
+mod material;
 mod shader;
 mod transform;
 
 pub use children::*;
 pub use input::*;
+pub use layer::*;
+pub use material::*;
 pub use shader::*;
 pub use transform::*;
 
+use web_sys::WebGl2RenderingContext;
+
+/// Lifecycle hooks are forwarded to every component, see `Scene` for the order they run in.
 pub trait HasComponents {
     fn update_components(&mut self, dt: f32);
+
+    fn start_components(&mut self) {}
+
+    fn enable_components(&mut self) {}
+
+    fn disable_components(&mut self) {}
+
+    fn destroy_components(&mut self, _gl: &WebGl2RenderingContext) {}
+
+    fn late_update_components(&mut self, _dt: f32) {}
+
+    fn fixed_update_components(&mut self, _dt: f32) {}
 }
 
 impl<U: HasComponents> HasComponents for Box<U> {
     fn update_components(&mut self, dt: f32) {
         self.as_mut().update_components(dt);
     }
+
+    fn start_components(&mut self) {
+        self.as_mut().start_components();
+    }
+
+    fn enable_components(&mut self) {
+        self.as_mut().enable_components();
+    }
+
+    fn disable_components(&mut self) {
+        self.as_mut().disable_components();
+    }
+
+    fn destroy_components(&mut self, gl: &WebGl2RenderingContext) {
+        self.as_mut().destroy_components(gl);
+    }
+
+    fn late_update_components(&mut self, dt: f32) {
+        self.as_mut().late_update_components(dt);
+    }
+
+    fn fixed_update_components(&mut self, dt: f32) {
+        self.as_mut().fixed_update_components(dt);
+    }
 }
 
 pub trait HasComponent<T: Component>: HasComponents {
@@ -28,4 +71,22 @@ pub trait HasComponent<T: Component>: HasComponents {
 
 pub trait Component {
     fn update(&mut self, dt: f32);
+
+    /// Before the first update of the object.
+    fn on_start(&mut self) {}
+
+    /// When the object is added to a scene or enabled again.
+    fn on_enable(&mut self) {}
+
+    /// When the object is disabled or about to be destroyed.
+    fn on_disable(&mut self) {}
+
+    /// When the object leaves the scene, GPU resources and subscriptions are released here.
+    fn on_destroy(&mut self, _gl: &WebGl2RenderingContext) {}
+
+    /// After every object updated.
+    fn late_update(&mut self, _dt: f32) {}
+
+    /// Once per fixed timestep of the scene, possibly several times a frame.
+    fn fixed_update(&mut self, _dt: f32) {}
 }
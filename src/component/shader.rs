@@ -1,21 +1,43 @@
 use crate::component::Component;
-use web_sys::WebGlProgram;
+use crate::render::{build_program, ShaderProgram};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::{WebGl2RenderingContext, WebGlProgram};
 
+/// Shared handle to a program, clones see the program swapped in by `set_program` or `reload`.
 #[derive(Default, Clone, Debug)]
 pub struct Shader {
-    program: Option<WebGlProgram>,
+    program: Rc<RefCell<Option<ShaderProgram>>>,
 }
 
 impl Shader {
-    pub fn get_program(&self) -> &WebGlProgram {
-        self.program.as_ref().expect("No program set for shader. Did you attach_component(shader)?")
+    pub fn get_program(&self) -> ShaderProgram {
+        self.program.borrow().clone().expect("No program set for shader. Did you attach_component(shader)?")
+    }
+
+    /// Replaces the program of every clone of this shader, deleting the previous one.
+    pub fn set_program(&self, gl: &WebGl2RenderingContext, program: WebGlProgram) {
+        let program = ShaderProgram::new(gl, program);
+
+        if let Some(previous) = self.program.borrow_mut().replace(program) {
+            gl.delete_program(Some(previous.get_program()));
+        }
+    }
+
+    /// Compiles and links new sources in place, keeping the current program when they fail.
+    pub fn reload(&self, gl: &WebGl2RenderingContext, vertex: &str, fragment: &str) -> Result<(), String> {
+        let program = build_program(gl, vertex, fragment)?;
+
+        self.set_program(gl, program);
+
+        Ok(())
     }
 }
 
-impl From<WebGlProgram> for Shader {
-    fn from(program: WebGlProgram) -> Self {
+impl From<ShaderProgram> for Shader {
+    fn from(program: ShaderProgram) -> Self {
         Shader {
-            program: Some(program)
+            program: Rc::new(RefCell::new(Some(program)))
         }
     }
 }
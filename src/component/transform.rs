@@ -109,6 +109,42 @@ impl Transform {
         return [matrix[0], matrix[4], matrix[8]];
     }
 
+    /// Direction a camera using this transform looks towards.
+    pub fn get_view_direction(&self) -> [f32; 3] {
+        let rotation = self.get_rotation();
+
+        let pitch = rotation[0].to_radians();
+        let yaw = rotation[1].to_radians();
+
+        [-yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos()]
+    }
+
+    /// Rotates so that `get_view_direction` points along `direction`, keeping the roll.
+    pub fn look_towards(&mut self, direction: [f32; 3]) {
+        let length = (direction[0] * direction[0] + direction[1] * direction[1] + direction[2] * direction[2]).sqrt();
+
+        if length == 0. {
+            return;
+        }
+
+        let pitch = (-direction[1] / length).asin().to_degrees();
+        let yaw = (-direction[0]).atan2(direction[2]).to_degrees();
+
+        let roll = self.get_rotation()[2];
+
+        self.set_rotation([pitch, yaw, roll]);
+    }
+
+    pub fn look_at(&mut self, target: [f32; 3]) {
+        let position = self.get_position();
+
+        self.look_towards([
+            target[0] - position[0],
+            target[1] - position[1],
+            target[2] - position[2],
+        ]);
+    }
+
     fn calculate_matrix(&self) -> [f32; 16] {
         let mut matrix = mat4::new_identity::<f32>();
 
@@ -1,13 +1,56 @@
+use web_sys::WebGl2RenderingContext;
+
+/// Lifecycle hooks are forwarded to every controller, see `Scene` for the order they run in.
 pub trait HasControllers {
     fn update_controllers(&mut self, dt: f32);
+
+    fn start_controllers(&mut self) {}
+
+    fn enable_controllers(&mut self) {}
+
+    fn disable_controllers(&mut self) {}
+
+    fn destroy_controllers(&mut self, _gl: &WebGl2RenderingContext) {}
+
+    fn late_update_controllers(&mut self, _dt: f32) {}
+
+    fn fixed_update_controllers(&mut self, _dt: f32) {}
+
+    /// Type names of the attached controllers, for the registry to report those it cannot save.
+    fn get_controller_types(&self) -> Vec<&'static str> {
+        vec![]
+    }
 }
 
 pub trait HasController<S: HasControllers, T: Controller<S>>: HasControllers {
     fn attach_controller(&mut self, controller: T);
 
     fn get_controller(&self) -> &T;
+
+    /// False while an optional controller is not attached, `get_controller` would panic.
+    fn has_controller(&self) -> bool {
+        true
+    }
 }
 
 pub trait Controller<P: HasControllers> {
     fn update(&mut self, parent: &mut P, dt: f32);
-}
\ No newline at end of file
+
+    /// Before the first update of the parent.
+    fn on_start(&mut self, _parent: &mut P) {}
+
+    /// When the parent is added to a scene or enabled again.
+    fn on_enable(&mut self, _parent: &mut P) {}
+
+    /// When the parent is disabled or about to be destroyed.
+    fn on_disable(&mut self, _parent: &mut P) {}
+
+    /// When the parent leaves the scene, GPU resources and subscriptions are released here.
+    fn on_destroy(&mut self, _parent: &mut P, _gl: &WebGl2RenderingContext) {}
+
+    /// After every object updated, e.g. cameras following an object that moved this frame.
+    fn late_update(&mut self, _parent: &mut P, _dt: f32) {}
+
+    /// Once per fixed timestep of the scene, possibly several times a frame.
+    fn fixed_update(&mut self, _parent: &mut P, _dt: f32) {}
+}
@@ -1,6 +1,11 @@
 #[macro_export]
 macro_rules! log {
     ( $( $t:tt )* ) => {
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::log_1(&format!( $( $t )* ).into());
+
+        // Native builds only run the tests.
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!( $( $t )* );
     }
 }
\ No newline at end of file
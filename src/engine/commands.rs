@@ -0,0 +1,87 @@
+use crate::builder::Builder;
+use crate::component::Component;
+use crate::engine::Entity;
+use crate::model::ObjectModel;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+/// Entity of a spawn command, known once the scene applied it.
+#[derive(Default, Clone, Debug)]
+pub struct Spawned {
+    entity: Rc<Cell<Option<Entity>>>,
+}
+
+impl Spawned {
+    pub fn get(&self) -> Option<Entity> {
+        self.entity.get()
+    }
+
+    pub(crate) fn set(&self, entity: Entity) {
+        self.entity.set(Some(entity));
+    }
+}
+
+/// Queued change, applied by `Scene::apply_commands`.
+pub enum Command {
+    SPAWN(Box<dyn ObjectModel>, Spawned),
+    DESPAWN(Entity),
+    ENABLE(Entity),
+    DISABLE(Entity),
+}
+
+/// Queue of scene changes shared with controllers.
+///
+/// Commands are applied by the scene between the update and the render of the frame,
+/// so no object is added or removed while the scene iterates over them.
+#[derive(Default, Clone)]
+pub struct Commands {
+    queue: Rc<RefCell<Vec<Command>>>,
+}
+
+impl fmt::Debug for Commands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Commands")
+            .field("queued", &self.queue.borrow().len())
+            .finish()
+    }
+}
+
+impl Commands {
+    /// Adds the object to the scene, its renderer is initialized when it is applied.
+    pub fn spawn(&self, object: Box<dyn ObjectModel>) -> Spawned {
+        let spawned = Spawned::default();
+
+        self.queue.borrow_mut().push(Command::SPAWN(object, spawned.clone()));
+
+        spawned
+    }
+
+    pub fn spawn_builder<T: ObjectModel + 'static, B: Builder<T>>(&self, builder: B) -> Result<Spawned, &'static str> {
+        Ok(self.spawn(builder.boxed()?))
+    }
+
+    /// Removes the object from the scene, stale entities are ignored.
+    pub fn despawn(&self, entity: Entity) {
+        self.queue.borrow_mut().push(Command::DESPAWN(entity));
+    }
+
+    /// Enables or disables the object, see `Scene::set_enabled`.
+    pub fn set_enabled(&self, entity: Entity, enabled: bool) {
+        let command = if enabled { Command::ENABLE(entity) } else { Command::DISABLE(entity) };
+
+        self.queue.borrow_mut().push(command);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.borrow().is_empty()
+    }
+
+    pub(crate) fn drain(&self) -> Vec<Command> {
+        self.queue.borrow_mut().drain(..).collect()
+    }
+}
+
+impl Component for Commands {
+    fn update(&mut self, _dt: f32) {}
+}
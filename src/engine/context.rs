@@ -0,0 +1,14 @@
+use crate::engine::{Commands, EventBus, Registry};
+use crate::render::Selection;
+
+/// Shared handles a scene gives to the controllers loaded into it, see `Registry::register_controller_with`.
+///
+/// `events` must be the bus attached to the engine and `selection` the one of the scene's
+/// `Picking`, from `Picking::get_selection`.
+#[derive(Default, Clone, Debug)]
+pub struct SceneContext {
+    pub commands: Commands,
+    pub events: EventBus,
+    pub registry: Registry,
+    pub selection: Selection,
+}
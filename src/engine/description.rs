@@ -0,0 +1,356 @@
+use crate::component::{normalize_rotation, Component, Layer, Material, Transform};
+use crate::engine::{Prefab, PrefabInstance, FIXED_TIMESTEP};
+use crate::render::{get_default_lights, CameraRenderer, Light, Projection, Viewport};
+use ron::extensions::Extensions;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformDescription {
+    pub position: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Default for TransformDescription {
+    fn default() -> Self {
+        TransformDescription::from(&Transform::default())
+    }
+}
+
+impl TransformDescription {
+    /// Places `local` inside this transform by multiplying their matrices. A rotated child of a
+    /// non-uniformly scaled parent would be sheared, which a description cannot hold, so the
+    /// shear is dropped.
+    pub fn compose(&self, local: &TransformDescription) -> TransformDescription {
+        let mut parent = Transform::from(self);
+        let mut child = Transform::from(local);
+
+        parent.update(0.);
+        child.update(0.);
+
+        let mut matrix = mat4::new_identity::<f32>();
+
+        mat4::mul(&mut matrix, &parent.get_matrix(), &child.get_matrix());
+
+        TransformDescription::from_matrix(&matrix)
+    }
+
+    /// Inverse of `Transform::get_matrix`, which applies the rotations around z, x then y.
+    fn from_matrix(matrix: &[f32; 16]) -> TransformDescription {
+        let mut scale = [0f32; 3];
+        let mut axes = [[0f32; 3]; 3];
+
+        for (i, axis) in axes.iter_mut().enumerate() {
+            let column = [matrix[i * 4], matrix[i * 4 + 1], matrix[i * 4 + 2]];
+
+            scale[i] = (column[0] * column[0] + column[1] * column[1] + column[2] * column[2]).sqrt();
+
+            if scale[i] != 0. {
+                *axis = [column[0] / scale[i], column[1] / scale[i], column[2] / scale[i]];
+            }
+        }
+
+        let [x, y, z] = axes;
+
+        // A mirrored matrix keeps a proper rotation by flipping one axis into the scale.
+        let determinant = x[0] * (y[1] * z[2] - y[2] * z[1]) - y[0] * (x[1] * z[2] - x[2] * z[1])
+            + z[0] * (x[1] * y[2] - x[2] * y[1]);
+
+        let x = if determinant < 0. {
+            scale[0] = -scale[0];
+
+            [-x[0], -x[1], -x[2]]
+        } else {
+            x
+        };
+
+        // Rows of the rotation Rz * Rx * Ry, read from its columns x, y and z.
+        let pitch = y[2].clamp(-1., 1.).asin();
+
+        let (yaw, roll) = if y[2].abs() < 0.9999 {
+            ((-x[2]).atan2(z[2]), (-y[0]).atan2(y[1]))
+        } else {
+            (0., x[1].atan2(x[0]))
+        };
+
+        TransformDescription {
+            position: [matrix[12], matrix[13], matrix[14]],
+            rotation: normalize_rotation(&[pitch.to_degrees(), yaw.to_degrees(), roll.to_degrees()]),
+            scale,
+        }
+    }
+}
+
+impl From<&Transform> for TransformDescription {
+    fn from(transform: &Transform) -> Self {
+        TransformDescription {
+            position: *transform.get_position(),
+            rotation: *transform.get_rotation(),
+            scale: *transform.get_scale(),
+        }
+    }
+}
+
+impl From<&TransformDescription> for Transform {
+    fn from(description: &TransformDescription) -> Self {
+        let mut transform = Transform::default();
+
+        transform.set_position(description.position);
+        transform.set_rotation(description.rotation);
+        transform.set_scale(description.scale);
+
+        transform
+    }
+}
+
+/// Controller looked up by name in the `Registry`, `params` are its serialized fields.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControllerDescription {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectDescription {
+    /// Object type registered in the `Registry`, which also decides the mesh.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub transform: TransformDescription,
+    pub material: Material,
+    pub layer_mask: u32,
+    pub controllers: Vec<ControllerDescription>,
+    /// Disabled objects are loaded without running `on_enable`, see `Scene::set_enabled`.
+    pub enabled: bool,
+}
+
+impl Default for ObjectDescription {
+    fn default() -> Self {
+        ObjectDescription {
+            type_name: String::new(),
+            transform: TransformDescription::default(),
+            material: Material::default(),
+            layer_mask: Layer::default().get_mask(),
+            controllers: vec![],
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraDescription {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub transform: TransformDescription,
+    pub projection: Projection,
+    pub viewport: Viewport,
+    pub clear_color: Option<[f32; 4]>,
+    pub clear_depth: bool,
+    pub priority: i32,
+    pub layer_mask: u32,
+    pub controllers: Vec<ControllerDescription>,
+}
+
+impl Default for CameraDescription {
+    fn default() -> Self {
+        let renderer = CameraRenderer::default();
+
+        CameraDescription {
+            type_name: String::new(),
+            transform: TransformDescription::default(),
+            projection: *renderer.get_projection(),
+            viewport: renderer.get_viewport(),
+            clear_color: renderer.get_clear_color(),
+            clear_depth: renderer.get_clear_depth(),
+            priority: renderer.get_priority(),
+            layer_mask: renderer.get_layer_mask(),
+            controllers: vec![],
+        }
+    }
+}
+
+/// Serializable content of a scene, loaded with `SceneBuilder::load` and produced by `Scene::save`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneDescription {
+    /// Defined in the registry when the scene is loaded, before the instances are expanded.
+    pub prefabs: BTreeMap<String, Prefab>,
+    pub objects: Vec<ObjectDescription>,
+    pub instances: Vec<PrefabInstance>,
+    pub cameras: Vec<CameraDescription>,
+    pub lights: Vec<Light>,
+    pub collision_events: bool,
+    pub fixed_timestep: f32,
+}
+
+impl Default for SceneDescription {
+    fn default() -> Self {
+        SceneDescription {
+            prefabs: BTreeMap::new(),
+            objects: vec![],
+            instances: vec![],
+            cameras: vec![],
+            lights: get_default_lights(),
+            collision_events: false,
+            fixed_timestep: FIXED_TIMESTEP,
+        }
+    }
+}
+
+impl SceneDescription {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|error| format!("failed to parse scene: {}", error))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|error| format!("failed to serialize scene: {}", error))
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, String> {
+        ron::from_str(ron).map_err(|error| format!("failed to parse scene: {}", error))
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default().extensions(Extensions::IMPLICIT_SOME))
+            .map_err(|error| format!("failed to serialize scene: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: &str = include_str!("../../resources/scenes/main.ron");
+
+    fn transform(position: [f32; 3], rotation: [f32; 3], scale: [f32; 3]) -> TransformDescription {
+        TransformDescription {
+            position,
+            rotation,
+            scale,
+        }
+    }
+
+    fn matrix(description: &TransformDescription) -> [f32; 16] {
+        let mut transform = Transform::from(description);
+
+        transform.update(0.);
+        transform.get_matrix()
+    }
+
+    fn assert_matrix_eq(actual: [f32; 16], expected: [f32; 16]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn assert_composes(parent: TransformDescription, local: TransformDescription) {
+        let mut expected = mat4::new_identity::<f32>();
+
+        mat4::mul(&mut expected, &matrix(&parent), &matrix(&local));
+
+        assert_matrix_eq(matrix(&parent.compose(&local)), expected);
+    }
+
+    #[test]
+    fn compose_with_identity_keeps_local() {
+        let local = transform([1., 2., 3.], [10., 20., 30.], [2., 3., 4.]);
+        let composed = TransformDescription::default().compose(&local);
+
+        assert_matrix_eq(matrix(&composed), matrix(&local));
+    }
+
+    #[test]
+    fn rotated_parent_rotates_child() {
+        let parent = transform([0., 1., 0.], [0., 90., 0.], [2., 2., 2.]);
+        let local = transform([1., 0., 0.], [90., 0., 0.], [1., 1., 1.]);
+        let composed = parent.compose(&local);
+
+        for (a, e) in composed.position.iter().zip([0., 1., -2.].iter()) {
+            assert!((a - e).abs() < 1e-4, "{:?}", composed.position);
+        }
+
+        assert_ne!(composed.rotation, [90., 90., 0.]);
+        assert_composes(parent, local);
+    }
+
+    #[test]
+    fn compose_matches_matrix_product() {
+        let parents = [
+            transform([1., 2., 3.], [30., 45., 60.], [2., 2., 2.]),
+            transform([-4., 0., 1.], [0., 0., 90.], [1., 1., 1.]),
+            transform([0., 0., 0.], [90., 10., 0.], [3., 3., 3.]),
+        ];
+        let locals = [
+            transform([1., 0., 0.], [0., 0., 0.], [1., 2., 3.]),
+            transform([0., -2., 5.], [15., 250., 80.], [0.5, 1., 1.]),
+            transform([3., 3., 3.], [270., 0., 45.], [1., 1., 4.]),
+        ];
+
+        for parent in parents.iter() {
+            for local in locals.iter() {
+                assert_composes(*parent, *local);
+            }
+        }
+    }
+
+    #[test]
+    fn compose_scales_axes_of_an_unrotated_parent() {
+        let parent = transform([0., 0., 0.], [0., 0., 0.], [2., 1., 3.]);
+        let local = transform([1., 1., 1.], [0., 0., 0.], [2., 2., 2.]);
+        let composed = parent.compose(&local);
+
+        assert_eq!(composed.position, [2., 1., 3.]);
+        assert_eq!(composed.scale, [4., 2., 6.]);
+    }
+
+    #[test]
+    fn compose_keeps_mirrored_scale() {
+        let parent = transform([0., 0., 0.], [0., 30., 0.], [1., 1., 1.]);
+        let local = transform([0., 0., 0.], [0., 0., 0.], [-1., 1., 1.]);
+
+        assert_composes(parent, local);
+    }
+
+    #[test]
+    fn main_scene_parses() {
+        let description = SceneDescription::from_ron(MAIN).unwrap();
+
+        assert_eq!(description.cameras.len(), 1);
+        assert_eq!(description.cameras[0].controllers[0].type_name, "FPSController");
+        assert_eq!(description.objects[0].transform.scale, [5., 1., 5.]);
+        assert_eq!(description.instances[0].prefab, "Spinner");
+        assert!(description.prefabs.contains_key("Spinner"));
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let description = SceneDescription::from_ron(MAIN).unwrap();
+        let ron = description.to_ron().unwrap();
+
+        assert_eq!(SceneDescription::from_ron(&ron).unwrap(), description);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let description = SceneDescription::from_ron(MAIN).unwrap();
+        let json = description.to_json().unwrap();
+
+        assert_eq!(SceneDescription::from_json(&json).unwrap(), description);
+    }
+
+    #[test]
+    fn parse_errors_keep_the_position() {
+        let error = SceneDescription::from_json("{\n  \"objects\": 3\n}").unwrap_err();
+
+        assert!(error.contains("line 2"), "{}", error);
+
+        let error = SceneDescription::from_ron("(\n  objects: 3,\n)").unwrap_err();
+
+        assert!(error.contains("2:"), "{}", error);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::component::{Children, HasComponent, HasComponents};
+use crate::assets::Assets;
+use crate::component::{Children, Component, HasComponent, HasComponents, Input};
 use crate::controller::{HasControllers};
-use crate::engine::Scene;
+use crate::engine::{ChangeSceneEvent, EventBus, Scene, SceneChangeEvent};
+use crate::render::ShaderWatcher;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -11,6 +14,11 @@ use web_sys::{HtmlCanvasElement, WebGl2RenderingContext};
 pub struct Engine {
     scene_index: usize,
     scenes: Children<Scene>,
+    input: Option<Input>,
+    events: EventBus,
+    assets: Assets,
+    shader_watcher: Option<ShaderWatcher>,
+    initialized: HashSet<usize>,
 }
 
 impl Engine {
@@ -23,13 +31,15 @@ impl Engine {
         canvas: HtmlCanvasElement,
         gl: WebGl2RenderingContext,
     ) -> Result<(), &'static str> {
-        let scene_index = self.get_scene_index();
+        let events = self.events.clone();
 
         let scenes: &mut Children<Scene> = self.get_component_mut()?;
 
-        let current_scene = scenes.get_mut(scene_index);
+        for scene in scenes.iter_mut() {
+            scene.attach_component(events.clone());
+        }
 
-        current_scene.init(&gl);
+        self.init_scene(self.get_scene_index(), &gl)?;
 
         let f = Rc::new(RefCell::new(None));
 
@@ -57,25 +67,91 @@ impl Engine {
         Ok(())
     }
 
+    fn init_scene(&mut self, scene_index: usize, gl: &WebGl2RenderingContext) -> Result<(), &'static str> {
+        if self.initialized.insert(scene_index) {
+            let scenes: &mut Children<Scene> = self.get_component_mut()?;
+
+            scenes.get_mut(scene_index).init(gl);
+        }
+
+        Ok(())
+    }
+
+    /// Switches to the last scene requested with a `ChangeSceneEvent` this frame.
+    fn change_scene(&mut self, gl: &WebGl2RenderingContext) -> Result<(), &'static str> {
+        let previous = self.get_scene_index();
+
+        let requested = self.events.read::<ChangeSceneEvent>().last().map(|event| event.index);
+
+        let current = match requested {
+            Some(index) if index != previous && index < self.scenes.len() => index,
+            _ => return Ok(()),
+        };
+
+        // Commands queued by the scene being left would otherwise wait until it is active again.
+        self.scenes.get_mut(previous).apply_commands(gl);
+
+        // Leaving a scene releases its GPU resources, it is initialized again if it becomes active.
+        self.scenes.get_mut(previous).destroy(gl);
+        self.initialized.remove(&previous);
+
+        self.init_scene(current, gl)?;
+
+        self.scene_index = current;
+
+        self.events.emit(SceneChangeEvent { previous, current });
+
+        Ok(())
+    }
+
     fn update(
         &mut self,
         dt: f32,
         canvas: &HtmlCanvasElement,
         gl: &WebGl2RenderingContext,
     ) -> Result<(), &'static str> {
+        let dt = match self.input.as_ref() {
+            Some(input) => {
+                let dt = input.latch(dt);
+
+                for event in input.get_action_events() {
+                    self.events.emit(event);
+                }
+
+                dt
+            }
+            None => dt,
+        };
+
         self.update_components(dt);
 
         self.update_controllers(dt);
 
+        if let Some(shader_watcher) = self.shader_watcher.as_ref() {
+            shader_watcher.reload(gl);
+        }
+
         let scene_index = self.get_scene_index();
 
         let scenes: &mut Children<Scene> = self.get_component_mut()?;
 
         let current_scene = scenes.get_mut(scene_index);
 
-        current_scene.update_components(dt);
+        current_scene.update(dt);
+
+        current_scene.detect_collisions();
+
+        self.events.dispatch();
+
+        self.change_scene(gl)?;
+
+        let scene_index = self.get_scene_index();
+
+        let scenes: &mut Children<Scene> = self.get_component_mut()?;
+
+        let current_scene = scenes.get_mut(scene_index);
 
-        current_scene.update_controllers(dt);
+        current_scene.apply_commands(gl);
 
         let width = canvas.width() as f32;
 
@@ -90,7 +166,11 @@ impl Engine {
 }
 
 impl HasComponents for Engine {
-    fn update_components(&mut self, dt: f32) {}
+    fn update_components(&mut self, dt: f32) {
+        if let Some(shader_watcher) = self.shader_watcher.as_mut() {
+            shader_watcher.update(dt);
+        }
+    }
 }
 
 impl HasControllers for Engine {
@@ -111,6 +191,62 @@ impl HasComponent<Children<Scene>> for Engine {
     }
 }
 
+impl HasComponent<Input> for Engine {
+    fn attach_component(&mut self, component: Input) {
+        self.input = Some(component);
+    }
+
+    fn get_component(&self) -> Result<&Input, &'static str> {
+        self.input.as_ref().ok_or("No input attached to engine.")
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Input, &'static str> {
+        self.input.as_mut().ok_or("No input attached to engine.")
+    }
+}
+
+impl HasComponent<EventBus> for Engine {
+    fn attach_component(&mut self, component: EventBus) {
+        self.events = component;
+    }
+
+    fn get_component(&self) -> Result<&EventBus, &'static str> {
+        Ok(&self.events)
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut EventBus, &'static str> {
+        Ok(&mut self.events)
+    }
+}
+
+impl HasComponent<Assets> for Engine {
+    fn attach_component(&mut self, component: Assets) {
+        self.assets = component;
+    }
+
+    fn get_component(&self) -> Result<&Assets, &'static str> {
+        Ok(&self.assets)
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Assets, &'static str> {
+        Ok(&mut self.assets)
+    }
+}
+
+impl HasComponent<ShaderWatcher> for Engine {
+    fn attach_component(&mut self, component: ShaderWatcher) {
+        self.shader_watcher = Some(component);
+    }
+
+    fn get_component(&self) -> Result<&ShaderWatcher, &'static str> {
+        self.shader_watcher.as_ref().ok_or("No shader watcher attached to engine.")
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut ShaderWatcher, &'static str> {
+        self.shader_watcher.as_mut().ok_or("No shader watcher attached to engine.")
+    }
+}
+
 fn request_animation_frame(f: &Closure<dyn FnMut(f32)>) {
     web_sys::window()
         .expect("No window")
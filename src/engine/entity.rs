@@ -0,0 +1,183 @@
+/// Handle to an object of a scene. The generation changes when the slot is reused,
+/// so handles kept after a despawn do not resolve to the next object in that slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entity {
+    index: usize,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Slot arena handing out generational `Entity` handles.
+pub struct Entities<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> Default for Entities<T> {
+    fn default() -> Self {
+        Entities {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+}
+
+impl<T> Entities<T> {
+    pub fn insert(&mut self, value: T) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+
+                slot.value = Some(value);
+
+                Entity {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+
+                Entity {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(entity.index)?;
+
+        if slot.generation != entity.generation {
+            return None;
+        }
+
+        let value = slot.value.take()?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+
+        self.free.push(entity.index);
+
+        Some(value)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.slots
+            .get(entity.index)
+            .filter(|slot| slot.generation == entity.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.slots
+            .get_mut(entity.index)
+            .filter(|slot| slot.generation == entity.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value.as_ref().map(|value| {
+                (
+                    Entity {
+                        index,
+                        generation: slot.generation,
+                    },
+                    value,
+                )
+            })
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+            let generation = slot.generation;
+
+            slot.value.as_mut().map(|value| (Entity { index, generation }, value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reused_slot_gets_a_new_generation() {
+        let mut entities = Entities::default();
+
+        let first = entities.insert("first");
+
+        assert_eq!(entities.remove(first), Some("first"));
+
+        let second = entities.insert("second");
+
+        assert_eq!(second.get_index(), first.get_index());
+        assert_ne!(second.get_generation(), first.get_generation());
+        assert_eq!(entities.get(second), Some(&"second"));
+    }
+
+    #[test]
+    fn stale_handles_do_not_resolve() {
+        let mut entities = Entities::default();
+
+        let stale = entities.insert(1);
+        entities.remove(stale);
+        let current = entities.insert(2);
+
+        assert!(!entities.contains(stale));
+        assert_eq!(entities.get(stale), None);
+        assert_eq!(entities.get_mut(stale), None);
+        assert_eq!(entities.remove(stale), None);
+
+        // Removing through the stale handle left the current object in place.
+        assert_eq!(entities.get(current), Some(&2));
+        assert_eq!(entities.len(), 1);
+    }
+
+    #[test]
+    fn iteration_skips_free_slots() {
+        let mut entities = Entities::default();
+
+        let a = entities.insert('a');
+        let b = entities.insert('b');
+        let c = entities.insert('c');
+
+        entities.remove(b);
+
+        let alive: Vec<(Entity, char)> = entities.iter().map(|(entity, value)| (entity, *value)).collect();
+
+        assert_eq!(alive, vec![(a, 'a'), (c, 'c')]);
+        assert_eq!(entities.len(), 2);
+    }
+}
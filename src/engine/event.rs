@@ -0,0 +1,224 @@
+use crate::component::Component;
+use crate::engine::Entity;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// An input action changed state this frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionEvent {
+    pub action: String,
+    pub pressed: bool,
+}
+
+/// Emit to ask the engine to switch scene at the end of the frame, the scene being left releases
+/// its GPU resources until it is active again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChangeSceneEvent {
+    pub index: usize,
+}
+
+/// The engine switched scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneChangeEvent {
+    pub previous: usize,
+    pub current: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionPhase {
+    ENTER,
+    EXIT,
+}
+
+/// World bounding boxes of two objects started or stopped overlapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+    pub phase: CollisionPhase,
+}
+
+type Subscriber<T> = Rc<RefCell<dyn FnMut(&T)>>;
+
+struct Channel<T> {
+    pending: Vec<T>,
+    delivered: Vec<T>,
+    subscribers: Vec<(usize, Subscriber<T>)>,
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Channel {
+            pending: vec![],
+            delivered: vec![],
+            subscribers: vec![],
+        }
+    }
+}
+
+trait Dispatch {
+    fn dispatch(&self);
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Clone + 'static> Dispatch for RefCell<Channel<T>> {
+    fn dispatch(&self) {
+        let (events, subscribers) = {
+            let mut channel = self.borrow_mut();
+
+            let events = std::mem::take(&mut channel.pending);
+
+            channel.delivered = events.clone();
+
+            let subscribers: Vec<Subscriber<T>> = channel
+                .subscribers
+                .iter()
+                .map(|(_, subscriber)| subscriber.clone())
+                .collect();
+
+            (events, subscribers)
+        };
+
+        // The channel is released so subscribers can emit, their events go out next frame.
+        for event in events.iter() {
+            for subscriber in subscribers.iter() {
+                (subscriber.borrow_mut())(event);
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Channels are dispatched in the order their type was first used, which is the same on every
+/// run as long as the game makes the same calls, as replays do. `TypeId` order is not.
+#[derive(Default)]
+struct EventBusState {
+    channels: Vec<Rc<dyn Dispatch>>,
+    indices: HashMap<TypeId, usize>,
+    next_subscription: usize,
+}
+
+/// Typed message queues shared between the engine, scenes and controllers.
+///
+/// Events emitted during a frame are delivered together by [`EventBus::dispatch`],
+/// after every controller updated and before rendering.
+#[derive(Default, Clone)]
+pub struct EventBus {
+    state: Rc<RefCell<EventBusState>>,
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("channels", &self.state.borrow().channels.len())
+            .finish()
+    }
+}
+
+impl EventBus {
+    fn with_channel<T: Clone + 'static, R>(&self, func: impl FnOnce(&mut Channel<T>) -> R) -> R {
+        let dispatch = {
+            let mut state = self.state.borrow_mut();
+            let EventBusState { channels, indices, .. } = &mut *state;
+
+            let index = *indices.entry(TypeId::of::<T>()).or_insert_with(|| {
+                channels.push(Rc::new(RefCell::new(Channel::<T>::default())));
+
+                channels.len() - 1
+            });
+
+            channels[index].clone()
+        };
+
+        let mut channel = dispatch
+            .as_any()
+            .downcast_ref::<RefCell<Channel<T>>>()
+            .expect("Channel registered with another type.")
+            .borrow_mut();
+
+        func(&mut channel)
+    }
+
+    pub fn emit<T: Clone + 'static>(&self, event: T) {
+        self.with_channel(|channel: &mut Channel<T>| channel.pending.push(event));
+    }
+
+    /// Calls `subscriber` for every event of type `T` when it is delivered.
+    pub fn subscribe<T: Clone + 'static>(&self, subscriber: impl FnMut(&T) + 'static) -> usize {
+        let id = {
+            let mut state = self.state.borrow_mut();
+
+            state.next_subscription += 1;
+
+            state.next_subscription
+        };
+
+        let subscriber: Subscriber<T> = Rc::new(RefCell::new(subscriber));
+
+        self.with_channel(|channel: &mut Channel<T>| channel.subscribers.push((id, subscriber)));
+
+        id
+    }
+
+    pub fn unsubscribe<T: Clone + 'static>(&self, id: usize) {
+        self.with_channel(|channel: &mut Channel<T>| channel.subscribers.retain(|(other, _)| *other != id));
+    }
+
+    /// Events of type `T` delivered by the last dispatch, for controllers polling each frame.
+    pub fn read<T: Clone + 'static>(&self) -> Vec<T> {
+        self.with_channel(|channel: &mut Channel<T>| channel.delivered.clone())
+    }
+
+    /// Delivers every pending event, called by the engine once per frame.
+    pub fn dispatch(&self) {
+        let channels: Vec<Rc<dyn Dispatch>> = self.state.borrow().channels.clone();
+
+        for channel in channels {
+            channel.dispatch();
+        }
+    }
+}
+
+impl Component for EventBus {
+    fn update(&mut self, _dt: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct First;
+
+    #[derive(Clone)]
+    struct Second;
+
+    #[test]
+    fn channels_dispatch_in_registration_order() {
+        let bus = EventBus::default();
+        let order = Rc::new(RefCell::new(vec![]));
+
+        {
+            let order = order.clone();
+            bus.subscribe(move |_: &Second| order.borrow_mut().push("second"));
+        }
+
+        {
+            let order = order.clone();
+            bus.subscribe(move |_: &First| order.borrow_mut().push("first"));
+        }
+
+        bus.emit(First);
+        bus.emit(Second);
+        bus.dispatch();
+
+        assert_eq!(*order.borrow(), vec!["second", "first"]);
+    }
+}
@@ -1,5 +1,19 @@
 mod scene;
 mod engine;
+mod event;
+mod entity;
+mod commands;
+mod context;
+mod description;
+mod prefab;
+mod registry;
 
 pub use scene::*;
-pub use engine::*;
\ No newline at end of file
+pub use engine::*;
+pub use event::*;
+pub use entity::*;
+pub use commands::*;
+pub use context::*;
+pub use description::*;
+pub use prefab::*;
+pub use registry::*;
\ No newline at end of file
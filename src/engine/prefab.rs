@@ -0,0 +1,81 @@
+use crate::component::Material;
+use crate::engine::{ObjectDescription, TransformDescription};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prefabs instantiating each other deeper than this are rejected as cycles.
+pub const MAX_PREFAB_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PrefabPart {
+    OBJECT(ObjectDescription),
+    /// Nested prefab, expanded with its own overrides before those of the outer instance.
+    PREFAB(PrefabInstance),
+}
+
+/// Reusable group of objects, defined with `Registry::define_prefab` or in a scene file.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    pub parts: Vec<PrefabPart>,
+}
+
+/// Fields of the objects of an instance replaced or adjusted when they are set.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrefabOverrides {
+    /// Placement of the instance, the transforms of the parts are relative to it.
+    pub transform: Option<TransformDescription>,
+    pub material: Option<Material>,
+    pub layer_mask: Option<u32>,
+    /// Fields merged into the params of the controllers of the type used as key.
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+impl PrefabOverrides {
+    pub fn apply(&self, object: &mut ObjectDescription) {
+        if let Some(transform) = self.transform.as_ref() {
+            object.transform = transform.compose(&object.transform);
+        }
+
+        if let Some(material) = self.material {
+            object.material = material;
+        }
+
+        if let Some(layer_mask) = self.layer_mask {
+            object.layer_mask = layer_mask;
+        }
+
+        for controller in object.controllers.iter_mut() {
+            let fields = match self.params.get(&controller.type_name) {
+                Some(serde_json::Value::Object(fields)) => fields,
+                _ => continue,
+            };
+
+            if !controller.params.is_object() {
+                controller.params = serde_json::Value::Object(Default::default());
+            }
+
+            let params = controller.params.as_object_mut().unwrap();
+
+            for (key, value) in fields {
+                params.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrefabInstance {
+    pub prefab: String,
+    #[serde(default)]
+    pub overrides: PrefabOverrides,
+}
+
+impl PrefabInstance {
+    pub fn new(prefab: &str) -> Self {
+        PrefabInstance {
+            prefab: prefab.to_string(),
+            overrides: PrefabOverrides::default(),
+        }
+    }
+}
@@ -0,0 +1,601 @@
+use crate::component::{Component, Layer, Material, Transform};
+use crate::controller::{Controller, HasController, HasControllers};
+use crate::engine::{
+    CameraDescription, ControllerDescription, ObjectDescription, Prefab, PrefabInstance, PrefabPart, SceneContext,
+    Spawned, TransformDescription, MAX_PREFAB_DEPTH,
+};
+use crate::log;
+use crate::model::{CameraModel, ObjectModel};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+type Params = serde_json::Value;
+
+type LoadController<T> = Box<dyn Fn(&mut T, Params, &SceneContext) -> Result<(), String>>;
+
+/// `None` when the controller is not attached.
+type SaveController<T> = Box<dyn Fn(&T) -> Option<Result<Params, &'static str>>>;
+
+struct ControllerType<T> {
+    name: String,
+    /// Rust type of the controller, matched against `HasControllers::get_controller_types`.
+    rust_type: &'static str,
+    load: LoadController<T>,
+    save: SaveController<T>,
+}
+
+/// Factory of a registered object or camera type with the controllers it can load.
+struct Type<T> {
+    factory: Box<dyn Fn() -> Result<T, &'static str>>,
+    controllers: Vec<ControllerType<T>>,
+}
+
+impl<T: HasControllers> Type<T> {
+    fn load_controllers(
+        &self,
+        target: &mut T,
+        descriptions: &[ControllerDescription],
+        context: &SceneContext,
+    ) -> Result<(), String> {
+        for description in descriptions {
+            let controller = self
+                .controllers
+                .iter()
+                .find(|controller| controller.name == description.type_name)
+                .ok_or_else(|| format!("unregistered controller type {}", description.type_name))?;
+
+            // Controllers without params are written as `null` or left out.
+            let params = match &description.params {
+                Params::Null => Params::Object(Default::default()),
+                params => params.clone(),
+            };
+
+            (controller.load)(target, params, context).map_err(|error| format!("{}: {}", description.type_name, error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fails when a controller is attached that the registry cannot save, rather than losing it.
+    fn save_controllers(&self, target: &T) -> Result<Vec<ControllerDescription>, &'static str> {
+        for attached in target.get_controller_types() {
+            if !self.controllers.iter().any(|controller| controller.rust_type == attached) {
+                log!("Cannot save unregistered controller {}", attached);
+
+                return Err("unregistered controller type");
+            }
+        }
+
+        let mut descriptions = vec![];
+
+        for controller in self.controllers.iter() {
+            if let Some(params) = (controller.save)(target) {
+                descriptions.push(ControllerDescription {
+                    type_name: controller.name.clone(),
+                    params: params?,
+                });
+            }
+        }
+
+        Ok(descriptions)
+    }
+}
+
+trait ObjectType {
+    fn load(&self, description: &ObjectDescription, context: &SceneContext) -> Result<Box<dyn ObjectModel>, String>;
+
+    /// `None` when the object is of another type.
+    fn save(&self, name: &str, object: &dyn ObjectModel) -> Option<Result<ObjectDescription, &'static str>>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: ObjectModel + 'static> ObjectType for Type<T> {
+    fn load(&self, description: &ObjectDescription, context: &SceneContext) -> Result<Box<dyn ObjectModel>, String> {
+        let mut object = (self.factory)()?;
+
+        object.attach_component(Transform::from(&description.transform));
+        object.attach_component(description.material);
+        object.attach_component(Layer::from_mask(description.layer_mask));
+
+        self.load_controllers(&mut object, &description.controllers, context)?;
+
+        Ok(Box::new(object))
+    }
+
+    fn save(&self, name: &str, object: &dyn ObjectModel) -> Option<Result<ObjectDescription, &'static str>> {
+        let object = object.as_any().downcast_ref::<T>()?;
+
+        let transform: &Transform = object.get_component().unwrap();
+        let material: &Material = object.get_component().unwrap();
+
+        let controllers = match self.save_controllers(object) {
+            Ok(controllers) => controllers,
+            Err(error) => return Some(Err(error)),
+        };
+
+        Some(Ok(ObjectDescription {
+            type_name: name.to_string(),
+            transform: TransformDescription::from(transform),
+            material: *material,
+            layer_mask: object.get_layer_mask(),
+            controllers,
+            enabled: true,
+        }))
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+trait CameraType {
+    fn load(&self, description: &CameraDescription, context: &SceneContext) -> Result<Box<dyn CameraModel>, String>;
+
+    /// `None` when the camera is of another type.
+    fn save(&self, name: &str, camera: &dyn CameraModel) -> Option<Result<CameraDescription, &'static str>>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: CameraModel + 'static> CameraType for Type<T> {
+    fn load(&self, description: &CameraDescription, context: &SceneContext) -> Result<Box<dyn CameraModel>, String> {
+        let mut camera = (self.factory)()?;
+
+        camera.attach_component(Transform::from(&description.transform));
+
+        let renderer = camera.get_renderer_mut();
+
+        renderer.set_projection(description.projection);
+        renderer.set_viewport(description.viewport);
+        renderer.set_clear_color(description.clear_color);
+        renderer.set_clear_depth(description.clear_depth);
+        renderer.set_priority(description.priority);
+        renderer.set_layer_mask(description.layer_mask);
+
+        self.load_controllers(&mut camera, &description.controllers, context)?;
+
+        Ok(Box::new(camera))
+    }
+
+    fn save(&self, name: &str, camera: &dyn CameraModel) -> Option<Result<CameraDescription, &'static str>> {
+        let camera = camera.as_any().downcast_ref::<T>()?;
+
+        let transform: &Transform = camera.get_component().unwrap();
+        let renderer = camera.get_renderer();
+
+        let controllers = match self.save_controllers(camera) {
+            Ok(controllers) => controllers,
+            Err(error) => return Some(Err(error)),
+        };
+
+        Some(Ok(CameraDescription {
+            type_name: name.to_string(),
+            transform: TransformDescription::from(transform),
+            projection: *renderer.get_projection(),
+            viewport: renderer.get_viewport(),
+            clear_color: renderer.get_clear_color(),
+            clear_depth: renderer.get_clear_depth(),
+            priority: renderer.get_priority(),
+            layer_mask: renderer.get_layer_mask(),
+            controllers,
+        }))
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct RegistryState {
+    objects: BTreeMap<String, Box<dyn ObjectType>>,
+    cameras: BTreeMap<String, Box<dyn CameraType>>,
+    prefabs: BTreeMap<String, Prefab>,
+}
+
+/// Maps the type names of a `SceneDescription` to object, camera and controller constructors,
+/// and holds the prefabs they are instantiated from.
+///
+/// Factories attach what a description does not hold, such as shaders and input;
+/// the transform, material, layer and controllers are then applied from the description.
+/// The registry is shared, so controllers can keep a clone to instantiate prefabs at runtime.
+#[derive(Default, Clone)]
+pub struct Registry {
+    state: Rc<RefCell<RegistryState>>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+
+        f.debug_struct("Registry")
+            .field("objects", &state.objects.keys().collect::<Vec<_>>())
+            .field("cameras", &state.cameras.keys().collect::<Vec<_>>())
+            .field("prefabs", &state.prefabs.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Registry {
+    pub fn register_object<T: ObjectModel + 'static>(
+        &self,
+        name: &str,
+        factory: impl Fn() -> Result<T, &'static str> + 'static,
+    ) {
+        self.state.borrow_mut().objects.insert(
+            name.to_string(),
+            Box::new(Type {
+                factory: Box::new(factory),
+                controllers: vec![],
+            }),
+        );
+    }
+
+    pub fn register_camera<T: CameraModel + 'static>(
+        &self,
+        name: &str,
+        factory: impl Fn() -> Result<T, &'static str> + 'static,
+    ) {
+        self.state.borrow_mut().cameras.insert(
+            name.to_string(),
+            Box::new(Type {
+                factory: Box::new(factory),
+                controllers: vec![],
+            }),
+        );
+    }
+
+    /// Lets objects or cameras registered as `parent` load and save the controller `C` as `name`.
+    pub fn register_controller<T, C>(&self, parent: &str, name: &str) -> Result<(), &'static str>
+    where
+        T: HasControllers + HasController<T, C> + 'static,
+        C: Controller<T> + Serialize + DeserializeOwned + 'static,
+    {
+        self.register_controller_with::<T, C>(parent, name, |_, _| {})
+    }
+
+    /// Like `register_controller`, with `inject` giving loaded controllers the handles of the
+    /// scene they are loaded into, which are not part of their saved fields.
+    pub fn register_controller_with<T, C>(
+        &self,
+        parent: &str,
+        name: &str,
+        inject: impl Fn(&mut C, &SceneContext) + 'static,
+    ) -> Result<(), &'static str>
+    where
+        T: HasControllers + HasController<T, C> + 'static,
+        C: Controller<T> + Serialize + DeserializeOwned + 'static,
+    {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+
+        let registered = match state.objects.get_mut(parent) {
+            Some(object_type) => object_type.as_any_mut(),
+            None => state
+                .cameras
+                .get_mut(parent)
+                .ok_or("unregistered parent type")?
+                .as_any_mut(),
+        };
+
+        let registered: &mut Type<T> = registered.downcast_mut().ok_or("parent registered with another type")?;
+
+        registered.controllers.push(ControllerType {
+            name: name.to_string(),
+            rust_type: std::any::type_name::<C>(),
+            load: Box::new(move |target: &mut T, params, context| {
+                let mut controller: C = serde_json::from_value(params).map_err(|error| error.to_string())?;
+
+                inject(&mut controller, context);
+
+                target.attach_controller(controller);
+
+                Ok(())
+            }),
+            save: Box::new(|target: &T| {
+                if !HasController::<T, C>::has_controller(target) {
+                    return None;
+                }
+
+                let controller: &C = target.get_controller();
+
+                Some(serde_json::to_value(controller).map_err(|_| "failed to serialize controller"))
+            }),
+        });
+
+        Ok(())
+    }
+
+    /// Replaces any prefab of the same name.
+    pub fn define_prefab(&self, name: &str, prefab: Prefab) {
+        self.state.borrow_mut().prefabs.insert(name.to_string(), prefab);
+    }
+
+    pub fn get_prefab(&self, name: &str) -> Option<Prefab> {
+        self.state.borrow().prefabs.get(name).cloned()
+    }
+
+    pub fn get_prefabs(&self) -> BTreeMap<String, Prefab> {
+        self.state.borrow().prefabs.clone()
+    }
+
+    /// Objects of an instance with nested prefabs resolved and every override applied.
+    pub fn expand_prefab(&self, instance: &PrefabInstance) -> Result<Vec<ObjectDescription>, String> {
+        self.expand_prefab_at(instance, 0)
+    }
+
+    fn expand_prefab_at(&self, instance: &PrefabInstance, depth: usize) -> Result<Vec<ObjectDescription>, String> {
+        if depth == MAX_PREFAB_DEPTH {
+            return Err(format!("prefab {} nested too deep", instance.prefab));
+        }
+
+        let prefab = self
+            .get_prefab(&instance.prefab)
+            .ok_or_else(|| format!("undefined prefab {}", instance.prefab))?;
+
+        let mut objects = vec![];
+
+        for part in prefab.parts.iter() {
+            match part {
+                PrefabPart::OBJECT(object) => objects.push(object.clone()),
+                PrefabPart::PREFAB(nested) => objects.extend(self.expand_prefab_at(nested, depth + 1)?),
+            }
+        }
+
+        for object in objects.iter_mut() {
+            instance.overrides.apply(object);
+        }
+
+        Ok(objects)
+    }
+
+    pub fn instantiate(&self, instance: &PrefabInstance, context: &SceneContext) -> Result<Vec<Box<dyn ObjectModel>>, String> {
+        self.expand_prefab(instance)?
+            .iter()
+            .map(|object| self.load_object(object, context))
+            .collect()
+    }
+
+    /// Queues the objects of an instance on the commands of `context`, for controllers adding
+    /// prefabs during a frame.
+    pub fn spawn(&self, instance: &PrefabInstance, context: &SceneContext) -> Result<Vec<Spawned>, String> {
+        Ok(self
+            .instantiate(instance, context)?
+            .into_iter()
+            .map(|object| context.commands.spawn(object))
+            .collect())
+    }
+
+    pub fn load_object(&self, description: &ObjectDescription, context: &SceneContext) -> Result<Box<dyn ObjectModel>, String> {
+        self.state
+            .borrow()
+            .objects
+            .get(&description.type_name)
+            .ok_or_else(|| format!("unregistered object type {}", description.type_name))?
+            .load(description, context)
+    }
+
+    pub fn load_camera(&self, description: &CameraDescription, context: &SceneContext) -> Result<Box<dyn CameraModel>, String> {
+        self.state
+            .borrow()
+            .cameras
+            .get(&description.type_name)
+            .ok_or_else(|| format!("unregistered camera type {}", description.type_name))?
+            .load(description, context)
+    }
+
+    pub fn save_object(&self, object: &dyn ObjectModel) -> Result<ObjectDescription, &'static str> {
+        self.state
+            .borrow()
+            .objects
+            .iter()
+            .find_map(|(name, object_type)| object_type.save(name, object))
+            .unwrap_or(Err("unregistered object type"))
+    }
+
+    pub fn save_camera(&self, camera: &dyn CameraModel) -> Result<CameraDescription, &'static str> {
+        self.state
+            .borrow()
+            .cameras
+            .iter()
+            .find_map(|(name, camera_type)| camera_type.save(name, camera))
+            .unwrap_or(Err("unregistered camera type"))
+    }
+}
+
+impl Component for Registry {
+    fn update(&mut self, _dt: f32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PrefabOverrides;
+    use serde_json::json;
+
+    fn object(type_name: &str, position: [f32; 3]) -> ObjectDescription {
+        ObjectDescription {
+            type_name: type_name.to_string(),
+            transform: TransformDescription {
+                position,
+                ..TransformDescription::default()
+            },
+            ..ObjectDescription::default()
+        }
+    }
+
+    fn nested(prefab: &str, position: [f32; 3]) -> PrefabPart {
+        let mut instance = PrefabInstance::new(prefab);
+
+        instance.overrides.transform = Some(TransformDescription {
+            position,
+            ..TransformDescription::default()
+        });
+
+        PrefabPart::PREFAB(instance)
+    }
+
+    fn chain(registry: &Registry, length: usize) {
+        for i in 0..length {
+            let part = if i + 1 == length {
+                PrefabPart::OBJECT(object("Cube", [0., 0., 0.]))
+            } else {
+                PrefabPart::PREFAB(PrefabInstance::new(&format!("Link{}", i + 1)))
+            };
+
+            registry.define_prefab(&format!("Link{}", i), Prefab { parts: vec![part] });
+        }
+    }
+
+    #[test]
+    fn nested_prefabs_are_expanded_in_place() {
+        let registry = Registry::default();
+
+        registry.define_prefab(
+            "Wheel",
+            Prefab {
+                parts: vec![PrefabPart::OBJECT(object("Cube", [0., 1., 0.]))],
+            },
+        );
+        registry.define_prefab(
+            "Cart",
+            Prefab {
+                parts: vec![
+                    PrefabPart::OBJECT(object("Body", [0., 0., 0.])),
+                    nested("Wheel", [-1., 0., 0.]),
+                    nested("Wheel", [1., 0., 0.]),
+                ],
+            },
+        );
+
+        let mut instance = PrefabInstance::new("Cart");
+
+        instance.overrides.transform = Some(TransformDescription {
+            position: [0., 0., 10.],
+            ..TransformDescription::default()
+        });
+
+        let objects = registry.expand_prefab(&instance).unwrap();
+        let positions: Vec<_> = objects.iter().map(|object| object.transform.position).collect();
+
+        assert_eq!(objects[0].type_name, "Body");
+        assert_eq!(objects[1].type_name, "Cube");
+        assert_eq!(positions, vec![[0., 0., 10.], [-1., 1., 10.], [1., 1., 10.]]);
+    }
+
+    #[test]
+    fn deep_prefabs_are_rejected_as_cycles() {
+        let registry = Registry::default();
+
+        chain(&registry, MAX_PREFAB_DEPTH);
+
+        assert_eq!(registry.expand_prefab(&PrefabInstance::new("Link0")).unwrap().len(), 1);
+
+        chain(&registry, MAX_PREFAB_DEPTH + 1);
+
+        assert!(registry.expand_prefab(&PrefabInstance::new("Link0")).is_err());
+
+        registry.define_prefab(
+            "Loop",
+            Prefab {
+                parts: vec![PrefabPart::PREFAB(PrefabInstance::new("Loop"))],
+            },
+        );
+
+        let error = registry.expand_prefab(&PrefabInstance::new("Loop")).unwrap_err();
+
+        assert!(error.contains("nested too deep"), "{}", error);
+    }
+
+    #[test]
+    fn undefined_prefab_is_named() {
+        let error = Registry::default().expand_prefab(&PrefabInstance::new("Missing")).unwrap_err();
+
+        assert!(error.contains("Missing"), "{}", error);
+    }
+
+    #[test]
+    fn overrides_apply_after_nested_overrides() {
+        let registry = Registry::default();
+        let mut cube = object("Cube", [0., 0., 0.]);
+
+        cube.controllers.push(ControllerDescription {
+            type_name: "Rotate".to_string(),
+            params: json!({ "speed": 1.0, "axis": 1 }),
+        });
+        cube.controllers.push(ControllerDescription {
+            type_name: "Tracked".to_string(),
+            params: serde_json::Value::Null,
+        });
+
+        registry.define_prefab(
+            "Spinner",
+            Prefab {
+                parts: vec![PrefabPart::OBJECT(cube)],
+            },
+        );
+
+        let mut inner = PrefabInstance::new("Spinner");
+
+        inner.overrides.material = Some(Material { color: [1., 0., 0.] });
+        inner.overrides.layer_mask = Some(2);
+        inner.overrides.params.insert("Rotate".to_string(), json!({ "speed": 2.0 }));
+
+        registry.define_prefab(
+            "Fast",
+            Prefab {
+                parts: vec![PrefabPart::PREFAB(inner)],
+            },
+        );
+
+        let mut outer = PrefabInstance::new("Fast");
+
+        outer.overrides = PrefabOverrides {
+            layer_mask: Some(4),
+            ..PrefabOverrides::default()
+        };
+        outer.overrides.params.insert("Rotate".to_string(), json!({ "speed": 3.0 }));
+        outer.overrides.params.insert("Tracked".to_string(), json!({ "id": 7 }));
+
+        let objects = registry.expand_prefab(&outer).unwrap();
+
+        assert_eq!(objects[0].material.color, [1., 0., 0.]);
+        assert_eq!(objects[0].layer_mask, 4);
+        assert_eq!(objects[0].controllers[0].params, json!({ "speed": 3.0, "axis": 1 }));
+        assert_eq!(objects[0].controllers[1].params, json!({ "id": 7 }));
+    }
+
+    #[test]
+    fn rotated_instance_rotates_its_parts() {
+        let registry = Registry::default();
+
+        registry.define_prefab(
+            "Arm",
+            Prefab {
+                parts: vec![PrefabPart::OBJECT(object("Cube", [2., 0., 0.]))],
+            },
+        );
+
+        let mut instance = PrefabInstance::new("Arm");
+
+        instance.overrides.transform = Some(TransformDescription {
+            rotation: [0., 0., 90.],
+            ..TransformDescription::default()
+        });
+
+        let objects = registry.expand_prefab(&instance).unwrap();
+        let transform = objects[0].transform;
+
+        for (a, e) in transform.position.iter().zip([0., 2., 0.].iter()) {
+            assert!((a - e).abs() < 1e-4, "{:?}", transform.position);
+        }
+
+        assert!((transform.rotation[2] - 90.).abs() < 1e-3, "{:?}", transform.rotation);
+    }
+}
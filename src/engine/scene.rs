@@ -1,50 +1,520 @@
-use crate::component::{HasComponents, Children, HasComponent};
+use crate::component::{Component, HasComponents, Children, HasComponent};
 use crate::controller::HasControllers;
+use crate::engine::{CollisionEvent, CollisionPhase, Command, Commands, Entities, Entity, EventBus, Registry, SceneDescription};
+use crate::log;
 use crate::model::{CameraModel, ObjectModel};
-use crate::render::Renderable;
+use crate::render::{
+    get_default_lights, CameraUniforms, FrameUniforms, Light, LightData, Picking, Ray, UniformBuffer,
+    CAMERA_BINDING, FRAME_BINDING,
+};
+use std::collections::BTreeSet;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlCanvasElement, WebGl2RenderingContext};
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct RenderStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RaycastHit {
+    pub entity: Entity,
+    pub point: [f32; 3],
+    pub normal: [f32; 3],
+    pub distance: f32,
+}
+
+/// Default fixed timestep, in milliseconds like the frame dt.
+pub const FIXED_TIMESTEP: f32 = 1000. / 60.;
+
+/// Fixed steps run in a single frame at most, the remaining time is dropped after a stall.
+const MAX_FIXED_STEPS: u32 = 8;
+
+struct SceneObject {
+    object: Box<dyn ObjectModel>,
+    enabled: bool,
+    started: bool,
+}
+
+impl SceneObject {
+    fn new(object: Box<dyn ObjectModel>) -> Self {
+        SceneObject {
+            object,
+            enabled: true,
+            started: false,
+        }
+    }
+
+    fn enable(&mut self) {
+        self.object.enable_components();
+        self.object.enable_controllers();
+    }
+
+    fn disable(&mut self) {
+        self.object.disable_controllers();
+        self.object.disable_components();
+    }
+
+    fn destroy(&mut self, gl: &WebGl2RenderingContext) {
+        if self.enabled {
+            self.disable();
+        }
+
+        self.object.destroy_controllers(gl);
+        self.object.destroy_components(gl);
+    }
+}
+
+/// Objects and cameras drawn together.
+///
+/// Every hook runs on components before controllers, except `on_disable` and `on_destroy`
+/// which run in reverse so controllers release what they hold first. A frame runs:
+///
+/// 1. `on_start` of the objects and cameras that have not started yet,
+/// 2. `fixed_update` once per elapsed fixed timestep,
+/// 3. `update` of cameras then objects,
+/// 4. `late_update` of cameras then objects,
+/// 5. the queued commands: spawns run `init` then `on_enable`, despawns run `on_disable`
+///    then `on_destroy`.
+///
+/// Disabled objects are skipped by every hook but `on_destroy`, and are not drawn, picked,
+/// raycast or collided.
 pub struct Scene {
-    objects: Children<Box<dyn ObjectModel>>,
+    objects: Entities<SceneObject>,
     cameras: Children<Box<dyn CameraModel>>,
+    started_cameras: usize,
+    fixed_timestep: f32,
+    fixed_time: f32,
+    /// Seconds since the scene started updating, `u_time` in shaders.
+    time: f32,
+    lights: Vec<Light>,
+    frame_uniforms: Option<UniformBuffer>,
+    camera_uniforms: Option<UniformBuffer>,
+    render_stats: RenderStats,
+    picking: Option<Picking>,
+    events: Option<EventBus>,
+    collision_events: bool,
+    overlaps: BTreeSet<(Entity, Entity)>,
+    commands: Commands,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene {
+            objects: Entities::default(),
+            cameras: Children::default(),
+            started_cameras: 0,
+            fixed_timestep: FIXED_TIMESTEP,
+            fixed_time: 0.,
+            time: 0.,
+            lights: get_default_lights(),
+            frame_uniforms: None,
+            camera_uniforms: None,
+            render_stats: RenderStats::default(),
+            picking: None,
+            events: None,
+            collision_events: false,
+            overlaps: BTreeSet::new(),
+            commands: Commands::default(),
+        }
+    }
 }
 
 impl Scene {
-    pub fn push_object(&mut self, object: Box<dyn ObjectModel>) {
-        self.objects.push(object);
+    pub fn push_object(&mut self, object: Box<dyn ObjectModel>) -> Entity {
+        self.objects.insert(SceneObject::new(object))
+    }
+
+    /// Adds an object that stays disabled until `set_enabled`, without running `on_disable`.
+    pub fn push_disabled_object(&mut self, object: Box<dyn ObjectModel>) -> Entity {
+        let mut scene_object = SceneObject::new(object);
+
+        scene_object.enabled = false;
+
+        self.objects.insert(scene_object)
+    }
+
+    pub fn get_object(&self, entity: Entity) -> Option<&dyn ObjectModel> {
+        self.objects.get(entity).map(|scene_object| scene_object.object.as_ref())
+    }
+
+    pub fn get_object_mut(&mut self, entity: Entity) -> Option<&mut (dyn ObjectModel + 'static)> {
+        self.objects.get_mut(entity).map(|scene_object| scene_object.object.as_mut())
+    }
+
+    /// Whether the entity still refers to an object of the scene.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.objects.contains(entity)
+    }
+
+    pub fn is_enabled(&self, entity: Entity) -> bool {
+        self.objects.get(entity).is_some_and(|scene_object| scene_object.enabled)
+    }
+
+    /// Runs `on_enable` or `on_disable` when the state changes, false for stale entities.
+    pub fn set_enabled(&mut self, entity: Entity, enabled: bool) -> bool {
+        let scene_object = match self.objects.get_mut(entity) {
+            Some(scene_object) => scene_object,
+            None => return false,
+        };
+
+        if scene_object.enabled != enabled {
+            scene_object.enabled = enabled;
+
+            if enabled {
+                scene_object.enable();
+            } else {
+                scene_object.disable();
+            }
+        }
+
+        true
+    }
+
+    /// Timestep of `fixed_update` in milliseconds.
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: f32) -> Result<(), &'static str> {
+        if fixed_timestep <= 0. {
+            return Err("fixed timestep must be positive");
+        }
+
+        self.fixed_timestep = fixed_timestep;
+
+        Ok(())
+    }
+
+    pub fn get_fixed_timestep(&self) -> f32 {
+        self.fixed_timestep
+    }
+
+    /// Applies the queued commands, called by the engine between update and render.
+    pub fn apply_commands(&mut self, gl: &WebGl2RenderingContext) {
+        for command in self.commands.drain() {
+            match command {
+                Command::SPAWN(mut object, spawned) => {
+                    object.init(gl);
+
+                    let mut scene_object = SceneObject::new(object);
+
+                    scene_object.enable();
+
+                    spawned.set(self.objects.insert(scene_object));
+                }
+                Command::DESPAWN(entity) => {
+                    if let Some(mut scene_object) = self.objects.remove(entity) {
+                        scene_object.destroy(gl);
+                    }
+                }
+                Command::ENABLE(entity) => {
+                    self.set_enabled(entity, true);
+                }
+                Command::DISABLE(entity) => {
+                    self.set_enabled(entity, false);
+                }
+            }
+        }
+    }
+
+    /// Runs one frame of hooks in the order documented on `Scene`.
+    pub fn update(&mut self, dt: f32) {
+        self.start();
+
+        self.time += dt / 1000.;
+
+        self.fixed_time += dt;
+
+        let mut steps = 0;
+
+        while self.fixed_time >= self.fixed_timestep {
+            if steps == MAX_FIXED_STEPS {
+                self.fixed_time %= self.fixed_timestep;
+
+                break;
+            }
+
+            self.fixed_update(self.fixed_timestep);
+
+            self.fixed_time -= self.fixed_timestep;
+
+            steps += 1;
+        }
+
+        self.update_components(dt);
+
+        self.update_controllers(dt);
+
+        self.late_update(dt);
+    }
+
+    fn start(&mut self) {
+        for index in self.started_cameras..self.cameras.len() {
+            let camera = self.cameras.get_mut(index);
+
+            camera.start_components();
+            camera.start_controllers();
+        }
+
+        self.started_cameras = self.cameras.len();
+
+        for (_, scene_object) in self.objects.iter_mut() {
+            if scene_object.enabled && !scene_object.started {
+                scene_object.object.start_components();
+                scene_object.object.start_controllers();
+
+                scene_object.started = true;
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, dt: f32) {
+        for camera in self.cameras.iter_mut() {
+            camera.fixed_update_components(dt);
+            camera.fixed_update_controllers(dt);
+        }
+
+        for (_, scene_object) in self.objects.iter_mut() {
+            if scene_object.enabled {
+                scene_object.object.fixed_update_components(dt);
+                scene_object.object.fixed_update_controllers(dt);
+            }
+        }
+    }
+
+    fn late_update(&mut self, dt: f32) {
+        for camera in self.cameras.iter_mut() {
+            camera.late_update_components(dt);
+            camera.late_update_controllers(dt);
+        }
+
+        for (_, scene_object) in self.objects.iter_mut() {
+            if scene_object.enabled {
+                scene_object.object.late_update_components(dt);
+                scene_object.object.late_update_controllers(dt);
+            }
+        }
+    }
+
+    /// Runs `on_disable` and `on_destroy` of every object and camera and releases the GPU
+    /// resources of the scene. Called by the engine on the scene it switches away from, the
+    /// objects and cameras are kept, so `init` brings the scene back and they start again.
+    pub fn destroy(&mut self, gl: &WebGl2RenderingContext) {
+        for (_, scene_object) in self.objects.iter_mut() {
+            scene_object.destroy(gl);
+            scene_object.started = false;
+        }
+
+        for camera in self.cameras.iter_mut() {
+            camera.disable_controllers();
+            camera.disable_components();
+            camera.destroy_controllers(gl);
+            camera.destroy_components(gl);
+        }
+
+        if let Some(picking) = self.picking.as_mut() {
+            picking.on_destroy(gl);
+        }
+
+        for uniforms in [self.frame_uniforms.take(), self.camera_uniforms.take()].iter_mut().flatten() {
+            uniforms.delete(gl);
+        }
+
+        self.started_cameras = 0;
+
+        self.overlaps.clear();
     }
     
     pub fn push_camera(&mut self, camera: Box<dyn CameraModel>) {
         self.cameras.push(camera);
     }
 
+    pub fn set_lights(&mut self, lights: Vec<Light>) {
+        self.lights = lights;
+    }
+
+    pub fn get_lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    /// Current state of the scene, every object, camera and attached controller type must be in the registry.
+    pub fn save(&self, registry: &Registry) -> Result<SceneDescription, &'static str> {
+        let mut objects = vec![];
+
+        for (_, scene_object) in self.objects.iter() {
+            let mut object = registry.save_object(scene_object.object.as_ref())?;
+
+            object.enabled = scene_object.enabled;
+
+            objects.push(object);
+        }
+
+        let mut cameras = vec![];
+
+        for camera in self.cameras.iter() {
+            cameras.push(registry.save_camera(camera.as_ref())?);
+        }
+
+        // Instances were expanded when they were loaded, their objects are saved as they are now.
+        Ok(SceneDescription {
+            prefabs: registry.get_prefabs(),
+            objects,
+            instances: vec![],
+            cameras,
+            lights: self.lights.clone(),
+            collision_events: self.collision_events,
+            fixed_timestep: self.fixed_timestep,
+        })
+    }
+
     pub fn init(&mut self, gl: &WebGl2RenderingContext) {
-        for box_object in self.objects.iter_mut() {
-            let object = box_object.as_mut();
+        for (_, scene_object) in self.objects.iter_mut() {
+            scene_object.object.init(gl);
+
+            if scene_object.enabled {
+                scene_object.enable();
+            }
+        }
+
+        for camera in self.cameras.iter_mut() {
+            camera.enable_components();
+            camera.enable_controllers();
+        }
 
-            object.init(gl);
+        match (UniformBuffer::new(gl, FRAME_BINDING), UniformBuffer::new(gl, CAMERA_BINDING)) {
+            (Ok(frame_uniforms), Ok(camera_uniforms)) => {
+                self.frame_uniforms = Some(frame_uniforms);
+                self.camera_uniforms = Some(camera_uniforms);
+            }
+            (Err(error), _) | (_, Err(error)) => {
+                log!("Uniform buffers disabled: {}", error);
+            }
+        }
+
+        if let Some(picking) = self.picking.as_mut() {
+            if let Err(error) = picking.init(gl) {
+                log!("Picking disabled: {}", error);
+
+                self.picking = None;
+            }
         }
     }
 
-    pub fn set_canvas_dimensions(&mut self, width: f32, height: f32) {
-        let aspect = width / height;
+    /// Emits a `CollisionEvent` when the world bounding boxes of two objects start or stop overlapping.
+    pub fn set_collision_events(&mut self, collision_events: bool) {
+        self.collision_events = collision_events;
+
+        self.overlaps.clear();
+    }
+
+    pub fn has_collision_events(&self) -> bool {
+        self.collision_events
+    }
+
+    pub fn detect_collisions(&mut self) {
+        let events = match self.events.as_ref() {
+            Some(events) if self.collision_events => events,
+            _ => return,
+        };
+
+        let bounds: Vec<_> = self
+            .objects
+            .iter()
+            .filter(|(_, scene_object)| scene_object.enabled)
+            .map(|(entity, scene_object)| (entity, scene_object.object.get_world_bounds().aabb))
+            .collect();
+
+        let mut overlaps = BTreeSet::new();
+
+        for (i, (a, a_bounds)) in bounds.iter().enumerate() {
+            for (b, b_bounds) in bounds.iter().skip(i + 1) {
+                if a_bounds.intersects(b_bounds) {
+                    overlaps.insert((*a, *b));
+                }
+            }
+        }
+
+        for &(a, b) in overlaps.difference(&self.overlaps) {
+            events.emit(CollisionEvent {
+                a,
+                b,
+                phase: CollisionPhase::ENTER,
+            });
+        }
+
+        for &(a, b) in self.overlaps.difference(&overlaps) {
+            events.emit(CollisionEvent {
+                a,
+                b,
+                phase: CollisionPhase::EXIT,
+            });
+        }
+
+        self.overlaps = overlaps;
+    }
 
+    pub fn get_render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Ray through a canvas pixel from the top-most camera whose viewport contains it.
+    pub fn screen_point_to_ray(&mut self, x: f32, y: f32) -> Option<Ray> {
+        let camera_index = self.get_camera_at(x, y)?;
+
+        self.cameras.get_mut(camera_index).screen_point_to_ray(x, y)
+    }
+
+    pub fn raycast(&self, ray: &Ray) -> Option<RaycastHit> {
+        let mut closest: Option<RaycastHit> = None;
+
+        for (entity, scene_object) in self.objects.iter() {
+            if !scene_object.enabled {
+                continue;
+            }
+
+            if let Some(hit) = scene_object.object.raycast(ray) {
+                if closest.is_none_or(|c| hit.distance < c.distance) {
+                    closest = Some(RaycastHit {
+                        entity,
+                        point: ray.get_point(hit.distance),
+                        normal: hit.normal,
+                        distance: hit.distance,
+                    });
+                }
+            }
+        }
+
+        closest
+    }
+
+    pub fn set_canvas_dimensions(&mut self, width: f32, height: f32) {
         for camera in self.cameras.iter_mut() {
-            camera.as_mut().get_renderer_mut().set_aspect(aspect);
+            camera.as_mut().get_renderer_mut().set_canvas_max(width as u32, height as u32);
         }
     }
 
+    fn get_camera_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.cameras.len()).collect();
+
+        order.sort_by_key(|&index| self.cameras.get(index).get_renderer().get_priority());
+
+        order
+    }
+
+    fn get_camera_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.get_camera_order()
+            .into_iter()
+            .rev()
+            .find(|&index| self.cameras.get(index).get_renderer().contains_screen_point(x, y))
+    }
+
     pub fn render(&mut self, canvas: &HtmlCanvasElement, gl: &WebGl2RenderingContext) {
         gl.enable(WebGl2RenderingContext::DEPTH_TEST);
 
-        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
-
         gl.clear_color(0., 0., 0., 1.);
 
         gl.clear_depth(1.);
 
+        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+
         gl.enable(WebGl2RenderingContext::CULL_FACE);
 
         gl.cull_face(WebGl2RenderingContext::BACK);
@@ -59,17 +529,147 @@ impl Scene {
             camera.update_matrix();
         }
 
-        for camera in self.cameras.iter_mut() {
-            let camera_renderer = camera.as_mut().get_renderer_mut();
+        let mut render_stats = RenderStats::default();
+
+        if let Some(frame_uniforms) = self.frame_uniforms.as_mut() {
+            let frame = FrameUniforms {
+                time: self.time,
+                lights: LightData::new(&self.lights),
+            };
+
+            frame_uniforms.upload(gl, &frame.to_std140());
+        }
+
+        gl.enable(WebGl2RenderingContext::SCISSOR_TEST);
+
+        for camera_index in self.get_camera_order() {
+            let camera_renderer = self.cameras.get_mut(camera_index).as_mut().get_renderer_mut();
+
+            if let Some(camera_uniforms) = self.camera_uniforms.as_mut() {
+                camera_uniforms.upload(gl, &CameraUniforms::new(camera_renderer).to_std140());
+            }
 
             camera_renderer.attach_viewport(gl);
 
-            for box_object in self.objects.iter_mut() {
-                let object = box_object.as_mut();
-    
+            camera_renderer.clear(gl);
+
+            let frustum = camera_renderer.get_frustum();
+
+            for (_, scene_object) in self.objects.iter_mut() {
+                if !scene_object.enabled {
+                    continue;
+                }
+
+                let object = scene_object.object.as_mut();
+
+                if !camera_renderer.is_layer_visible(object.get_layer_mask()) {
+                    continue;
+                }
+
+                if !frustum.intersects(&object.get_world_bounds()) {
+                    render_stats.culled += 1;
+
+                    continue;
+                }
+
+                render_stats.drawn += 1;
+
                 object.render(gl, camera_renderer);
             }
         }
+
+        gl.disable(WebGl2RenderingContext::SCISSOR_TEST);
+
+        self.render_stats = render_stats;
+
+        self.render_picking(gl, width, height);
+    }
+
+    fn render_picking(&mut self, gl: &WebGl2RenderingContext, width: u32, height: u32) {
+        let (x, y) = match self.picking.as_ref() {
+            Some(picking) => picking.get_mouse_position(),
+            None => return,
+        };
+
+        let camera_index = self.get_camera_at(x, y);
+
+        let picking = self.picking.as_mut().unwrap();
+
+        if !picking.begin(gl, width, height) {
+            return;
+        }
+
+        if let Some(camera_index) = camera_index {
+            let camera_renderer = self.cameras.get_mut(camera_index).as_mut().get_renderer_mut();
+
+            if let Some(camera_uniforms) = self.camera_uniforms.as_mut() {
+                camera_uniforms.upload(gl, &CameraUniforms::new(camera_renderer).to_std140());
+            }
+
+            camera_renderer.attach_viewport(gl);
+
+            let frustum = camera_renderer.get_frustum();
+
+            for (entity, scene_object) in self.objects.iter_mut() {
+                if !scene_object.enabled {
+                    continue;
+                }
+
+                let object = scene_object.object.as_mut();
+
+                if !camera_renderer.is_layer_visible(object.get_layer_mask())
+                    || !frustum.intersects(&object.get_world_bounds())
+                {
+                    continue;
+                }
+
+                picking.render_object(gl, object, camera_renderer, entity);
+            }
+        }
+
+        picking.end(gl);
+    }
+}
+
+impl HasComponent<Picking> for Scene {
+    fn attach_component(&mut self, picking: Picking) {
+        self.picking = Some(picking);
+    }
+
+    fn get_component(&self) -> Result<&Picking, &'static str> {
+        self.picking.as_ref().ok_or("No picking attached to scene.")
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Picking, &'static str> {
+        self.picking.as_mut().ok_or("No picking attached to scene.")
+    }
+}
+
+impl HasComponent<EventBus> for Scene {
+    fn attach_component(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    fn get_component(&self) -> Result<&EventBus, &'static str> {
+        self.events.as_ref().ok_or("No event bus attached to scene.")
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut EventBus, &'static str> {
+        self.events.as_mut().ok_or("No event bus attached to scene.")
+    }
+}
+
+impl HasComponent<Commands> for Scene {
+    fn attach_component(&mut self, commands: Commands) {
+        self.commands = commands;
+    }
+
+    fn get_component(&self) -> Result<&Commands, &'static str> {
+        Ok(&self.commands)
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Commands, &'static str> {
+        Ok(&mut self.commands)
     }
 }
 
@@ -79,8 +679,10 @@ impl HasComponents for Scene {
             camera.as_mut().update_components(dt);
         }
 
-        for object in self.objects.iter_mut() {
-            object.as_mut().update_components(dt);
+        for (_, scene_object) in self.objects.iter_mut() {
+            if scene_object.enabled {
+                scene_object.object.update_components(dt);
+            }
         }
     }
 }
@@ -91,8 +693,433 @@ impl HasControllers for Scene {
             camera.as_mut().update_controllers(dt);
         }
 
-        for object in self.objects.iter_mut() {
-            object.as_mut().update_controllers(dt);
+        for (_, scene_object) in self.objects.iter_mut() {
+            if scene_object.enabled {
+                scene_object.object.update_controllers(dt);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Builder, SceneBuilder};
+    use crate::component::{Layer, Material, Shader, Transform};
+    use crate::controller::{Controller, HasController};
+    use crate::engine::{CameraDescription, ControllerDescription, ObjectDescription, Prefab, PrefabInstance, PrefabPart, SceneContext};
+    use crate::render::{Bounds, CameraRenderer, Indices, ShaderProgram};
+    use serde::{Deserialize, Serialize};
+    use std::any::Any;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::{JsCast, JsValue};
+
+    /// Hooks of these tests do not touch the context, a null one stands in for it natively.
+    fn null_context() -> WebGl2RenderingContext {
+        JsValue::NULL.unchecked_into()
+    }
+
+    #[derive(Default)]
+    struct LoggingCamera {
+        log: Rc<RefCell<Vec<&'static str>>>,
+        transform: Transform,
+        renderer: CameraRenderer,
+    }
+
+    impl HasComponents for LoggingCamera {
+        fn update_components(&mut self, _dt: f32) {
+            self.log.borrow_mut().push("update");
+        }
+
+        fn start_components(&mut self) {
+            self.log.borrow_mut().push("start");
+        }
+
+        fn late_update_components(&mut self, _dt: f32) {
+            self.log.borrow_mut().push("late_update");
+        }
+
+        fn fixed_update_components(&mut self, _dt: f32) {
+            self.log.borrow_mut().push("fixed_update");
+        }
+    }
+
+    impl HasControllers for LoggingCamera {
+        fn update_controllers(&mut self, _dt: f32) {}
+    }
+
+    impl HasComponent<Transform> for LoggingCamera {
+        fn attach_component(&mut self, transform: Transform) {
+            self.transform = transform;
+        }
+
+        fn get_component(&self) -> Result<&Transform, &'static str> {
+            Ok(&self.transform)
+        }
+
+        fn get_component_mut(&mut self) -> Result<&mut Transform, &'static str> {
+            Ok(&mut self.transform)
+        }
+    }
+
+    impl CameraModel for LoggingCamera {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_renderer(&self) -> &CameraRenderer {
+            &self.renderer
+        }
+
+        fn get_renderer_mut(&mut self) -> &mut CameraRenderer {
+            &mut self.renderer
+        }
+    }
+
+    /// Spawns its prefab on every update through the handles injected when it is loaded.
+    #[derive(Default, Serialize, Deserialize)]
+    struct Spawner {
+        prefab: String,
+        #[serde(skip)]
+        context: Option<SceneContext>,
+    }
+
+    impl Controller<StubObject> for Spawner {
+        fn update(&mut self, _parent: &mut StubObject, _dt: f32) {
+            let context = self.context.as_ref().unwrap();
+
+            context.registry.spawn(&PrefabInstance::new(&self.prefab), context).unwrap();
+        }
+    }
+
+    #[derive(Default)]
+    struct StubObject {
+        log: Rc<RefCell<Vec<&'static str>>>,
+        transform: Transform,
+        shader: Shader,
+        layer: Layer,
+        material: Material,
+        spawner: Option<Spawner>,
+    }
+
+    impl HasComponents for StubObject {
+        fn update_components(&mut self, _dt: f32) {}
+
+        fn start_components(&mut self) {
+            self.log.borrow_mut().push("object start");
+        }
+
+        fn enable_components(&mut self) {
+            self.log.borrow_mut().push("object enable");
+        }
+
+        fn disable_components(&mut self) {
+            self.log.borrow_mut().push("object disable");
+        }
+
+        fn destroy_components(&mut self, _gl: &WebGl2RenderingContext) {
+            self.log.borrow_mut().push("object destroy");
+        }
+    }
+
+    impl HasControllers for StubObject {
+        fn update_controllers(&mut self, dt: f32) {
+            if let Some(mut spawner) = self.spawner.take() {
+                spawner.update(self, dt);
+
+                self.spawner = Some(spawner);
+            }
+        }
+
+        fn get_controller_types(&self) -> Vec<&'static str> {
+            self.spawner.iter().map(|_| std::any::type_name::<Spawner>()).collect()
+        }
+    }
+
+    impl HasController<StubObject, Spawner> for StubObject {
+        fn attach_controller(&mut self, spawner: Spawner) {
+            self.spawner = Some(spawner);
+        }
+
+        fn get_controller(&self) -> &Spawner {
+            self.spawner.as_ref().unwrap()
+        }
+
+        fn has_controller(&self) -> bool {
+            self.spawner.is_some()
+        }
+    }
+
+    macro_rules! impl_stub_component {
+        ($type:ty, $field:ident) => {
+            impl HasComponent<$type> for StubObject {
+                fn attach_component(&mut self, component: $type) {
+                    self.$field = component;
+                }
+
+                fn get_component(&self) -> Result<&$type, &'static str> {
+                    Ok(&self.$field)
+                }
+
+                fn get_component_mut(&mut self) -> Result<&mut $type, &'static str> {
+                    Ok(&mut self.$field)
+                }
+            }
+        };
+    }
+
+    impl_stub_component!(Transform, transform);
+    impl_stub_component!(Shader, shader);
+    impl_stub_component!(Layer, layer);
+    impl_stub_component!(Material, material);
+
+    impl ObjectModel for StubObject {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn init_renderer(&mut self, _gl: &WebGl2RenderingContext) {}
+
+        fn render_renderer(&mut self, _gl: &WebGl2RenderingContext, _camera: &mut CameraRenderer) {}
+
+        fn get_program(&self) -> ShaderProgram {
+            self.shader.get_program()
         }
+
+        fn get_local_bounds(&self) -> Bounds {
+            Bounds::default()
+        }
+
+        fn get_vertices(&self) -> &[f32] {
+            &[]
+        }
+
+        fn get_indices(&self) -> Indices<'_> {
+            Indices::U16(&[])
+        }
+
+        // No program to validate the material against.
+        fn init(&mut self, _gl: &WebGl2RenderingContext) {}
+    }
+
+    fn create_scene() -> (Scene, Rc<RefCell<Vec<&'static str>>>) {
+        let camera = LoggingCamera::default();
+        let log = camera.log.clone();
+
+        let mut scene = Scene::default();
+
+        scene.set_fixed_timestep(10.).unwrap();
+        scene.push_camera(Box::new(camera));
+
+        (scene, log)
+    }
+
+    #[test]
+    fn hooks_run_in_frame_order() {
+        let (mut scene, log) = create_scene();
+
+        scene.update(25.);
+
+        assert_eq!(*log.borrow(), vec!["start", "fixed_update", "fixed_update", "update", "late_update"]);
+
+        log.borrow_mut().clear();
+
+        // The 5ms left over from the first frame complete a fixed step.
+        scene.update(5.);
+
+        assert_eq!(*log.borrow(), vec!["fixed_update", "update", "late_update"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fixed_steps_are_clamped_after_a_stall() {
+        let (mut scene, log) = create_scene();
+
+        scene.update(1000.);
+
+        let fixed_updates = |log: &[&str]| log.iter().filter(|&&hook| hook == "fixed_update").count();
+
+        assert_eq!(fixed_updates(&log.borrow()), MAX_FIXED_STEPS as usize);
+
+        log.borrow_mut().clear();
+
+        // The time past the clamp is dropped rather than caught up on.
+        scene.update(5.);
+
+        assert_eq!(fixed_updates(&log.borrow()), 0);
+    }
+
+    #[test]
+    fn objects_are_kept_when_the_scene_is_left() {
+        let (mut scene, log) = create_scene();
+        let gl = null_context();
+
+        let object = StubObject {
+            log: log.clone(),
+            ..StubObject::default()
+        };
+        let entity = scene.push_object(Box::new(object));
+
+        scene.update(1.);
+        log.borrow_mut().clear();
+
+        scene.destroy(&gl);
+
+        assert!(scene.is_alive(entity));
+        assert!(scene.is_enabled(entity));
+        assert_eq!(*log.borrow(), vec!["object disable", "object destroy"]);
+
+        log.borrow_mut().clear();
+
+        // Coming back starts the objects and cameras again.
+        scene.update(1.);
+
+        assert_eq!(*log.borrow(), vec!["start", "object start", "update", "late_update"]);
+    }
+
+    #[test]
+    fn commands_apply_between_frames() {
+        let (mut scene, log) = create_scene();
+        let gl = null_context();
+        let commands = scene.commands.clone();
+
+        let spawned = commands.spawn(Box::new(StubObject {
+            log: log.clone(),
+            ..StubObject::default()
+        }));
+
+        assert_eq!(spawned.get(), None);
+
+        scene.apply_commands(&gl);
+
+        let first = spawned.get().unwrap();
+
+        assert!(scene.is_alive(first));
+
+        commands.despawn(first);
+        scene.apply_commands(&gl);
+
+        assert!(!scene.is_alive(first));
+
+        // The slot of the despawned object is reused, the stale entity must not reach the new one.
+        let second = commands.spawn(Box::new(StubObject::default()));
+
+        scene.apply_commands(&gl);
+
+        let second = second.get().unwrap();
+
+        assert_eq!(second.get_index(), first.get_index());
+
+        commands.despawn(first);
+        scene.apply_commands(&gl);
+
+        assert!(scene.is_alive(second));
+        assert!(scene.get_object(first).is_none());
+    }
+
+    fn create_context() -> SceneContext {
+        let context = SceneContext::default();
+
+        context.registry.register_object("Stub", || Ok(StubObject::default()));
+        context.registry.register_camera("Logging", || Ok(LoggingCamera::default()));
+        context
+            .registry
+            .register_controller_with::<StubObject, Spawner>("Stub", "Spawner", |spawner, context| {
+                spawner.context = Some(context.clone());
+            })
+            .unwrap();
+
+        context
+    }
+
+    fn stub_description() -> ObjectDescription {
+        ObjectDescription {
+            type_name: "Stub".to_string(),
+            ..ObjectDescription::default()
+        }
+    }
+
+    fn spawner_description() -> SceneDescription {
+        let mut object = stub_description();
+
+        object.controllers.push(ControllerDescription {
+            type_name: "Spawner".to_string(),
+            params: serde_json::json!({ "prefab": "Ball" }),
+        });
+
+        let mut description = SceneDescription::default();
+
+        description.prefabs.insert(
+            "Ball".to_string(),
+            Prefab {
+                parts: vec![PrefabPart::OBJECT(stub_description())],
+            },
+        );
+        description.objects.push(object);
+
+        description
+    }
+
+    #[test]
+    fn loaded_controllers_can_queue_spawns() {
+        let context = create_context();
+        let gl = null_context();
+
+        let mut scene = SceneBuilder::default().load(&spawner_description(), &context).unwrap().build().unwrap();
+
+        scene.update(1.);
+
+        assert!(!context.commands.is_empty());
+
+        scene.apply_commands(&gl);
+
+        assert_eq!(scene.save(&context.registry).unwrap().objects.len(), 2);
+    }
+
+    #[test]
+    fn saved_scene_matches_the_loaded_description() {
+        let context = create_context();
+        let mut description = spawner_description();
+
+        let disabled = &mut description.objects[0];
+
+        disabled.enabled = false;
+        disabled.layer_mask = 6;
+        disabled.material = Material { color: [0.1, 0.2, 0.3] };
+        disabled.transform.position = [1., 2., 3.];
+        disabled.transform.rotation = [0., 90., 0.];
+
+        description.cameras.push(CameraDescription {
+            type_name: "Logging".to_string(),
+            priority: 2,
+            clear_color: Some([0., 0., 1., 1.]),
+            ..CameraDescription::default()
+        });
+        description.fixed_timestep = 10.;
+        description.collision_events = true;
+
+        let scene = SceneBuilder::default().load(&description, &context).unwrap().build().unwrap();
+
+        assert_eq!(scene.save(&context.registry).unwrap(), description);
+    }
+
+    #[test]
+    fn saving_an_unregistered_controller_fails() {
+        let registry = Registry::default();
+
+        registry.register_object("Stub", || Ok(StubObject::default()));
+
+        let mut scene = Scene::default();
+
+        scene.push_object(Box::new(StubObject::default()));
+
+        assert!(scene.save(&registry).is_ok());
+
+        scene.push_object(Box::new(StubObject {
+            spawner: Some(Spawner::default()),
+            ..StubObject::default()
+        }));
+
+        assert_eq!(scene.save(&registry).unwrap_err(), "unregistered controller type");
+    }
+}
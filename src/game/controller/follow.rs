@@ -0,0 +1,110 @@
+use crate::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Shared handle to the transform of an entity that can be followed.
+#[derive(Default, Clone, Debug)]
+pub struct FollowTarget {
+    transform: Rc<RefCell<Transform>>,
+}
+
+impl FollowTarget {
+    pub fn set_transform(&self, transform: &Transform) {
+        *self.transform.borrow_mut() = transform.clone();
+    }
+
+    pub fn get_position(&self) -> [f32; 3] {
+        *self.transform.borrow().get_position()
+    }
+
+    pub fn get_rotation(&self) -> [f32; 3] {
+        *self.transform.borrow().get_rotation()
+    }
+}
+
+/// Publishes the parent's transform to a `FollowTarget` every frame.
+#[derive(Default, Clone, Debug)]
+pub struct Tracked {
+    pub target: FollowTarget,
+}
+
+impl<T: HasControllers + HasComponent<Transform>> Controller<T> for Tracked {
+    fn update(&mut self, parent: &mut T, _dt: f32) {
+        let transform: &Transform = parent.get_component().unwrap();
+
+        self.target.set_transform(transform);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FollowController {
+    pub target: FollowTarget,
+    pub offset: [f32; 3],
+    pub damping: f32,
+    pub rotate_with_target: bool,
+    pub look_at_target: bool,
+}
+
+impl Default for FollowController {
+    fn default() -> Self {
+        FollowController {
+            target: FollowTarget::default(),
+            offset: [0., 5., -15.],
+            damping: 5.,
+            rotate_with_target: true,
+            look_at_target: true,
+        }
+    }
+}
+
+impl FollowController {
+    fn get_desired_position(&self) -> [f32; 3] {
+        let position = self.target.get_position();
+
+        let [x, y, z] = self.offset;
+
+        let offset = if self.rotate_with_target {
+            let yaw = self.target.get_rotation()[1].to_radians();
+
+            [x * yaw.cos() + z * yaw.sin(), y, z * yaw.cos() - x * yaw.sin()]
+        } else {
+            self.offset
+        };
+
+        [
+            position[0] + offset[0],
+            position[1] + offset[1],
+            position[2] + offset[2],
+        ]
+    }
+}
+
+/// Moves in `late_update`, once the target has moved for the frame.
+impl<T: HasControllers + HasComponent<Transform>> Controller<T> for FollowController {
+    fn update(&mut self, _parent: &mut T, _dt: f32) {}
+
+    fn late_update(&mut self, parent: &mut T, dt: f32) {
+        let desired = self.get_desired_position();
+
+        // dt is in milliseconds, damping is the convergence rate per second.
+        let t = if self.damping > 0. {
+            1. - (-self.damping * dt / 1000.).exp()
+        } else {
+            1.
+        };
+
+        let transform: &mut Transform = parent.get_component_mut().unwrap();
+
+        let position = *transform.get_position();
+
+        transform.set_position([
+            position[0] + (desired[0] - position[0]) * t,
+            position[1] + (desired[1] - position[1]) * t,
+            position[2] + (desired[2] - position[2]) * t,
+        ]);
+
+        if self.look_at_target {
+            transform.look_at(self.target.get_position());
+        }
+    }
+}
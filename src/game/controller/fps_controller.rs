@@ -1,7 +1,8 @@
-use crate::game::*;
 use crate::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FPSController {}
 
 impl<T: HasControllers + HasComponent<Input> + HasComponent<Transform>> Controller<T>
@@ -9,63 +10,42 @@ impl<T: HasControllers + HasComponent<Input> + HasComponent<Transform>> Controll
 {
     fn update(&mut self, parent: &mut T, dt: f32) {
         let input: &Input = parent.get_component().unwrap();
-        let mouse = input.get_mouse();
 
-        let dx = mouse.get_dx();
-        let dy = mouse.get_dy();
+        let mut dx = input.get_axis("turn_x");
+        let mut dy = input.get_axis("turn_y");
 
-        if mouse.is_down(MouseButton::PRIMARY) {
-            let transform: &mut Transform = parent.get_component_mut().unwrap();
-
-            transform.delta_rotation([dy as f32, dx as f32, 0.], dt / 2.0);
+        // Holding the look action is only needed while the pointer is free.
+        if input.get_mouse().is_pointer_locked() || input.is_action_down("look") {
+            dx += input.get_axis("look_x");
+            dy += input.get_axis("look_y");
         }
 
+        let transform: &mut Transform = parent.get_component_mut().unwrap();
+
+        transform.delta_rotation([dy, dx, 0.], dt / 2.0);
+
         let input: &Input = parent.get_component().unwrap();
-        let keyboard = input.get_keyboard();
+
+        let forward = input.get_axis("move_forward");
+        let right = input.get_axis("move_right");
+        let up = input.get_axis("move_up");
+
         let transform: &Transform = parent.get_component().unwrap();
 
-        let dx = if keyboard.is_down(KeyboardKey::FORWARD) {
+        let dx = {
             let dx = transform.get_forward();
 
-            [dx[0], 0., dx[2]]
-        } else if keyboard.is_down(KeyboardKey::BACKWARD) {
-            let dx = transform.get_forward();
-            
-            [-dx[0], 0., -dx[2]]
-        } else {
-            [0f32; 3]
+            [dx[0] * forward, 0., dx[2] * forward]
         };
 
-        let dy = if keyboard.is_down(KeyboardKey::UP) {
-            [0., 1., 0.]
-        } else if keyboard.is_down(KeyboardKey::DOWN) {
-            [0., -1., 0.]
-        } else {
-            [0.; 3]
-        };
+        let dy = [0., up, 0.];
 
-        let dz = if keyboard.is_down(KeyboardKey::LEFT) {
+        let dz = {
             let dx = transform.get_left();
 
-            [dx[0], 0., dx[2]]
-        } else if keyboard.is_down(KeyboardKey::RIGHT) {
-            let dx = transform.get_left();
-            
-            [-dx[0], 0., -dx[2]]
-        } else {
-            [0f32; 3]
+            [-dx[0] * right, 0., -dx[2] * right]
         };
 
-        let mut dp = [
-            dx[0] + dy[0] + dz[0],
-            dx[1] + dy[1] + dz[1],
-            dx[2] + dy[2] + dz[2],
-        ];
-
-        let dp_copy = dp.clone();
-
-        vec3::norm(&mut dp, &dp_copy);
-
         let transform: &mut Transform = parent.get_component_mut().unwrap();
 
         transform.delta_position(
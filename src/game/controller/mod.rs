@@ -1,5 +1,9 @@
+mod follow;
 mod fps_controller;
+mod orbit;
 mod rotate;
 
+pub use follow::*;
 pub use fps_controller::*;
+pub use orbit::*;
 pub use rotate::*;
\ No newline at end of file
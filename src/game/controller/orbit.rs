@@ -0,0 +1,88 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrbitController {
+    pub target: [f32; 3],
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub rotate_speed: f32,
+    pub zoom_speed: f32,
+    pub pan_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        OrbitController {
+            target: [0f32; 3],
+            distance: 30.,
+            yaw: 0.,
+            pitch: 30.,
+            rotate_speed: 0.25,
+            zoom_speed: 0.001,
+            pan_speed: 0.002,
+            min_distance: 1.,
+            max_distance: 500.,
+        }
+    }
+}
+
+impl<T: HasControllers + HasComponent<Input> + HasComponent<Transform>> Controller<T>
+    for OrbitController
+{
+    fn update(&mut self, parent: &mut T, _dt: f32) {
+        let input: &Input = parent.get_component().unwrap();
+        let mouse = input.get_mouse();
+        let touch = input.get_touch();
+
+        let dx = mouse.get_dx() as f32;
+        let dy = mouse.get_dy() as f32;
+
+        let (rotate_x, rotate_y) = if mouse.is_down(MouseButton::PRIMARY) {
+            (dx, dy)
+        } else {
+            (0., 0.)
+        };
+
+        let drag = touch.get_drag();
+
+        self.yaw += (rotate_x + drag[0]) * self.rotate_speed;
+        self.pitch = (self.pitch + (rotate_y + drag[1]) * self.rotate_speed).clamp(-89., 89.);
+
+        if mouse.is_down(MouseButton::AUXILIARY) {
+            let yaw = self.yaw.to_radians();
+            let pitch = self.pitch.to_radians();
+
+            let right = [-yaw.cos(), 0., -yaw.sin()];
+            let up = [-pitch.sin() * yaw.sin(), pitch.cos(), pitch.sin() * yaw.cos()];
+
+            let scale = self.pan_speed * self.distance;
+
+            for i in 0..3 {
+                self.target[i] += (-right[i] * dx + up[i] * dy) * scale;
+            }
+        }
+
+        let wheel = mouse.get_wheel() as f32;
+
+        // Spreading two fingers apart zooms in.
+        self.distance = (self.distance * (1. + wheel * self.zoom_speed) / touch.get_pinch())
+            .clamp(self.min_distance, self.max_distance);
+
+        let transform: &mut Transform = parent.get_component_mut().unwrap();
+
+        transform.set_rotation([self.pitch, self.yaw, 0.]);
+
+        let direction = transform.get_view_direction();
+
+        transform.set_position([
+            self.target[0] - direction[0] * self.distance,
+            self.target[1] - direction[1] * self.distance,
+            self.target[2] - direction[2] * self.distance,
+        ]);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::*;
 use crate::game::*;
+use serde::{Deserialize, Serialize};
 
-#[derive (Default, Clone, Debug)]
+#[derive (Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Rotate {
     pub speed: f32
 }
@@ -8,50 +8,117 @@ pub use crate::*;
 
 use wasm_bindgen::prelude::*;
 
+/// Files the game needs before it starts.
+struct GameAssets {
+    vertex: Handle<String>,
+    fragment: Handle<String>,
+    /// Files included by the shaders.
+    includes: Vec<Handle<String>>,
+    scene: Handle<SceneDescription>,
+}
+
 pub fn main(
     canvas: web_sys::HtmlCanvasElement,
     gl: web_sys::WebGl2RenderingContext,
 ) -> Result<(), JsValue> {
-    let program = link_program_str(
-        &gl,
-        include_str!("../../resources/shaders/vert.glsl"),
-        include_str!("../../resources/shaders/frag.glsl"),
-    );
-
     let input = Input::new(&canvas);
 
-    let shader = Shader::from(program);
-
-    EngineBuilder::default()
-        .attach_builder(
-            SceneBuilder::default()
-                .attach_builder(
-                    CameraBuilder::<Camera>::default()
-                        .attach_component(input.clone())?
-                        .attach_component_builder(
-                            TransformBuilder::default().set_position([0., 5., 0.])?,
-                        )?,
-                )?
-                .attach_builder(
-                    ObjectBuilder::<Cube>::default()
-                        .attach_component(shader.clone())?
-                        .attach_component_builder(
-                            TransformBuilder::default().set_scale([5., 1., 5.])?,
-                        )?,
-                )?
-                .attach_builder(
-                    ObjectBuilder::<Cube>::default()
-                        .attach_component(shader.clone())?
-                        .attach_controller(Rotate { speed: 2. })?
-                        .attach_component_builder(
-                            TransformBuilder::default()
-                                .set_position([-15., 0., 0.])?
-                                .set_scale([4., 4., 4.])?,
-                        )?,
-                )?,
-        )?
-        .build()?
-        .init(canvas, gl)?;
+    input.set_map(InputMap::from_json(include_str!("../../resources/input.json"))?);
+
+    input.get_mouse().set_lock_on_click(true);
+
+    if Touch::is_supported() {
+        let touch = input.get_touch();
+
+        touch.add_joystick(VirtualJoystick::new("move", [0.2, 0.75], 60.));
+        touch.add_button(VirtualButton::new("up", [0.85, 0.65], 35.));
+        touch.add_button(VirtualButton::new("down", [0.85, 0.85], 35.));
+        touch.set_controls_visible(true);
+    }
+
+    let assets = Assets::default();
+
+    let game_assets = GameAssets {
+        vertex: assets.load("resources/shaders/vert.glsl"),
+        fragment: assets.load("resources/shaders/frag.glsl"),
+        includes: vec![
+            assets.load("resources/shaders/camera.glsl"),
+            assets.load("resources/shaders/frame.glsl"),
+            assets.load("resources/shaders/lighting.glsl"),
+        ],
+        scene: assets.load("resources/scenes/main.ron"),
+    };
+
+    assets.on_loaded(move |assets| {
+        if let Err(error) = start(canvas, gl, input, assets, game_assets) {
+            log!("Failed to start: {:?}", error);
+        }
+    });
+
+    Ok(())
+}
+
+fn start(
+    canvas: web_sys::HtmlCanvasElement,
+    gl: web_sys::WebGl2RenderingContext,
+    input: Input,
+    assets: &Assets,
+    game_assets: GameAssets,
+) -> Result<(), JsValue> {
+    if let Some((path, error)) = assets.get_errors().first() {
+        return Err(format!("failed to load {}: {}", path, error).into());
+    }
+
+    let description = game_assets.scene.get().unwrap();
+
+    let mut preprocessor = Preprocessor::default();
+
+    for source in [&game_assets.vertex, &game_assets.fragment].iter().copied().chain(game_assets.includes.iter()) {
+        preprocessor.set_file(source.get_path(), &source.get().unwrap());
+    }
+
+    preprocessor.set_define("MAX_LIGHTS", MAX_LIGHTS);
+
+    let variants = ShaderVariants::new(preprocessor, game_assets.vertex.get_path(), game_assets.fragment.get_path());
+
+    let shader = variants.get(&gl, &[])?;
+
+    let registry = Registry::default();
+
+    registry.register_object("Cube", move || ObjectBuilder::<Cube>::default().attach_component(shader.clone())?.build());
+
+    {
+        let input = input.clone();
+
+        registry.register_camera("Camera", move || CameraBuilder::<Camera>::default().attach_component(input.clone())?.build());
+    }
+
+    registry.register_controller::<Cube, Rotate>("Cube", "Rotate")?;
+    registry.register_controller::<Camera, FPSController>("Camera", "FPSController")?;
+    registry.register_controller::<Camera, OrbitController>("Camera", "OrbitController")?;
+
+    let context = SceneContext {
+        registry,
+        ..SceneContext::default()
+    };
+
+    let engine = EngineBuilder::default()
+        .attach_component(input.clone())?
+        .attach_component(assets.clone())?
+        .attach_component(context.events.clone())?
+        .attach_builder(SceneBuilder::default().load(&description, &context)?)?;
+
+    // Edits to the shader files are recompiled into the running scene.
+    #[cfg(feature = "hot-reload")]
+    let engine = {
+        let shader_watcher = ShaderWatcher::default();
+
+        shader_watcher.watch_variants(&variants);
+
+        engine.attach_component(shader_watcher)?
+    };
+
+    engine.build()?.init(canvas, gl)?;
 
     Ok(())
 }
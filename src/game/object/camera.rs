@@ -1,15 +1,23 @@
 use crate::*;
 use crate::game::*;
+use std::any::Any;
+use web_sys::WebGl2RenderingContext;
 
 #[derive (Default, Clone, Debug)]
 pub struct Camera {
     transform: Transform,
     renderer: CameraRenderer,
-    fps_controller: FPSController,
+    fps_controller: Option<FPSController>,
+    orbit_controller: Option<OrbitController>,
+    follow_controller: Option<FollowController>,
     input: Input
 }
 
 impl CameraModel for Camera {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     fn get_renderer(&self) -> &CameraRenderer {
         &self.renderer
     } 
@@ -19,29 +27,145 @@ impl CameraModel for Camera {
     } 
 }
 
+impl Camera {
+    fn for_each_controller(&mut self, func: impl Fn(&mut dyn Controller<Camera>, &mut Camera)) {
+        if let Some(mut clone) = self.fps_controller.clone() {
+            func(&mut clone, self);
+
+            self.fps_controller = Some(clone);
+        }
+
+        if let Some(mut clone) = self.orbit_controller.clone() {
+            func(&mut clone, self);
+
+            self.orbit_controller = Some(clone);
+        }
+
+        if let Some(mut clone) = self.follow_controller.clone() {
+            func(&mut clone, self);
+
+            self.follow_controller = Some(clone);
+        }
+    }
+}
+
 impl HasComponents for Camera {
     fn update_components(&mut self, dt: f32) {
         self.transform.update(dt);
     }
+
+    fn start_components(&mut self) {
+        self.transform.on_start();
+    }
+
+    fn enable_components(&mut self) {
+        self.transform.on_enable();
+    }
+
+    fn disable_components(&mut self) {
+        self.transform.on_disable();
+    }
+
+    fn destroy_components(&mut self, gl: &WebGl2RenderingContext) {
+        self.transform.on_destroy(gl);
+    }
+
+    fn late_update_components(&mut self, dt: f32) {
+        self.transform.late_update(dt);
+    }
+
+    fn fixed_update_components(&mut self, dt: f32) {
+        self.transform.fixed_update(dt);
+    }
 }
 
 impl HasControllers for Camera {
     fn update_controllers(&mut self, dt: f32) {
-        let mut clone = self.fps_controller.clone();
+        self.for_each_controller(|controller, camera| controller.update(camera, dt));
+    }
+
+    fn start_controllers(&mut self) {
+        self.for_each_controller(|controller, camera| controller.on_start(camera));
+    }
+
+    fn enable_controllers(&mut self) {
+        self.for_each_controller(|controller, camera| controller.on_enable(camera));
+    }
 
-        clone.update(self, dt);
+    fn disable_controllers(&mut self) {
+        self.for_each_controller(|controller, camera| controller.on_disable(camera));
+    }
+
+    fn destroy_controllers(&mut self, gl: &WebGl2RenderingContext) {
+        self.for_each_controller(|controller, camera| controller.on_destroy(camera, gl));
+    }
+
+    fn late_update_controllers(&mut self, dt: f32) {
+        self.for_each_controller(|controller, camera| controller.late_update(camera, dt));
+    }
+
+    fn fixed_update_controllers(&mut self, dt: f32) {
+        self.for_each_controller(|controller, camera| controller.fixed_update(camera, dt));
+    }
 
-        self.fps_controller = clone;
+    fn get_controller_types(&self) -> Vec<&'static str> {
+        let mut types = vec![];
+
+        if self.fps_controller.is_some() {
+            types.push(std::any::type_name::<FPSController>());
+        }
+
+        if self.orbit_controller.is_some() {
+            types.push(std::any::type_name::<OrbitController>());
+        }
+
+        if self.follow_controller.is_some() {
+            types.push(std::any::type_name::<FollowController>());
+        }
+
+        types
     }
 }
 
 impl HasController<Camera, FPSController> for Camera {
     fn attach_controller(&mut self, controller: FPSController) {
-        self.fps_controller = controller;
+        self.fps_controller = Some(controller);
     }
 
     fn get_controller(&self) -> &FPSController {
-        &self.fps_controller
+        self.fps_controller.as_ref().expect("No FPSController attached to camera.")
+    }
+
+    fn has_controller(&self) -> bool {
+        self.fps_controller.is_some()
+    }
+}
+
+impl HasController<Camera, OrbitController> for Camera {
+    fn attach_controller(&mut self, controller: OrbitController) {
+        self.orbit_controller = Some(controller);
+    }
+
+    fn get_controller(&self) -> &OrbitController {
+        self.orbit_controller.as_ref().expect("No OrbitController attached to camera.")
+    }
+
+    fn has_controller(&self) -> bool {
+        self.orbit_controller.is_some()
+    }
+}
+
+impl HasController<Camera, FollowController> for Camera {
+    fn attach_controller(&mut self, controller: FollowController) {
+        self.follow_controller = Some(controller);
+    }
+
+    fn get_controller(&self) -> &FollowController {
+        self.follow_controller.as_ref().expect("No FollowController attached to camera.")
+    }
+
+    fn has_controller(&self) -> bool {
+        self.follow_controller.is_some()
     }
 }
 
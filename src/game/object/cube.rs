@@ -1,15 +1,23 @@
 use crate::*;
 use crate::game::*;
-use web_sys::{WebGlProgram, WebGl2RenderingContext};
+use std::any::Any;
+use web_sys::WebGl2RenderingContext;
 
 #[derive (Default, Clone, Debug)]
 pub struct Cube {
     transform: Transform,
     renderer: CubeRenderer,
-    rotate: Rotate
+    layer: Layer,
+    material: Material,
+    rotate: Option<Rotate>,
+    tracked: Option<Tracked>
 }
 
 impl ObjectModel for Cube {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     fn init_renderer(&mut self, gl: &WebGl2RenderingContext) {
         self.renderer.init(gl);
     }
@@ -18,11 +26,45 @@ impl ObjectModel for Cube {
         self.renderer.render(gl, camera);
     }
 
-    fn get_program(&self) -> &WebGlProgram {
+    fn get_program(&self) -> ShaderProgram {
         let shader: &Shader = self.get_component().unwrap();
 
         shader.get_program()
     }
+
+    fn get_local_bounds(&self) -> Bounds {
+        self.renderer.get_bounds()
+    }
+
+    fn get_vertices(&self) -> &[f32] {
+        self.renderer.get_vertices()
+    }
+
+    fn get_indices(&self) -> Indices<'_> {
+        self.renderer.get_indices()
+    }
+}
+
+impl Cube {
+    fn for_each_component(&mut self, func: impl Fn(&mut dyn Component)) {
+        func(&mut self.transform);
+        func(&mut self.layer);
+        func(&mut self.material);
+    }
+
+    fn for_each_controller(&mut self, func: impl Fn(&mut dyn Controller<Cube>, &mut Cube)) {
+        if let Some(mut clone) = self.rotate.clone() {
+            func(&mut clone, self);
+
+            self.rotate = Some(clone);
+        }
+
+        if let Some(mut clone) = self.tracked.clone() {
+            func(&mut clone, self);
+
+            self.tracked = Some(clone);
+        }
+    }
 }
 
 impl HasComponents for Cube {
@@ -30,25 +72,102 @@ impl HasComponents for Cube {
         self.transform.update(dt);
         self.renderer.update_components(dt);
     }
+
+    fn start_components(&mut self) {
+        self.for_each_component(|component| component.on_start());
+    }
+
+    fn enable_components(&mut self) {
+        self.for_each_component(|component| component.on_enable());
+    }
+
+    fn disable_components(&mut self) {
+        self.for_each_component(|component| component.on_disable());
+    }
+
+    // The cube buffers are shared by every cube and outlive a single one.
+    fn destroy_components(&mut self, gl: &WebGl2RenderingContext) {
+        self.for_each_component(|component| component.on_destroy(gl));
+    }
+
+    fn late_update_components(&mut self, dt: f32) {
+        self.for_each_component(|component| component.late_update(dt));
+    }
+
+    fn fixed_update_components(&mut self, dt: f32) {
+        self.for_each_component(|component| component.fixed_update(dt));
+    }
 }
 
 impl HasControllers for Cube {
     fn update_controllers(&mut self, dt: f32) {
-        let mut clone = self.rotate.clone();
+        self.for_each_controller(|controller, cube| controller.update(cube, dt));
+    }
+
+    fn start_controllers(&mut self) {
+        self.for_each_controller(|controller, cube| controller.on_start(cube));
+    }
+
+    fn enable_controllers(&mut self) {
+        self.for_each_controller(|controller, cube| controller.on_enable(cube));
+    }
 
-        clone.update(self, dt);
+    fn disable_controllers(&mut self) {
+        self.for_each_controller(|controller, cube| controller.on_disable(cube));
+    }
+
+    fn destroy_controllers(&mut self, gl: &WebGl2RenderingContext) {
+        self.for_each_controller(|controller, cube| controller.on_destroy(cube, gl));
+    }
+
+    fn late_update_controllers(&mut self, dt: f32) {
+        self.for_each_controller(|controller, cube| controller.late_update(cube, dt));
+    }
 
-        self.rotate = clone;
+    fn fixed_update_controllers(&mut self, dt: f32) {
+        self.for_each_controller(|controller, cube| controller.fixed_update(cube, dt));
+    }
+
+    fn get_controller_types(&self) -> Vec<&'static str> {
+        let mut types = vec![];
+
+        if self.rotate.is_some() {
+            types.push(std::any::type_name::<Rotate>());
+        }
+
+        if self.tracked.is_some() {
+            types.push(std::any::type_name::<Tracked>());
+        }
+
+        types
     }
 }
 
 impl HasController<Cube, Rotate> for Cube {
     fn attach_controller(&mut self, controller: Rotate) {
-        self.rotate = controller;
+        self.rotate = Some(controller);
     }
 
     fn get_controller(&self) -> &Rotate {
-        &self.rotate
+        self.rotate.as_ref().expect("No Rotate controller attached to cube.")
+    }
+
+    fn has_controller(&self) -> bool {
+        self.rotate.is_some()
+    }
+}
+
+impl HasController<Cube, Tracked> for Cube {
+    fn attach_controller(&mut self, controller: Tracked) {
+        self.tracked = Some(controller);
+    }
+
+    fn get_controller(&self) -> &Tracked {
+        self.tracked.as_ref().expect("No Tracked controller attached to cube.")
+    }
+
+    fn has_controller(&self) -> bool {
+        self.tracked.is_some()
     }
 }
 
@@ -78,4 +197,32 @@ impl HasComponent<Transform> for Cube {
     fn get_component_mut(&mut self) -> Result<&mut Transform, &'static str> {
         Ok(&mut self.transform)
     }
+}
+
+impl HasComponent<Layer> for Cube {
+    fn attach_component(&mut self, layer: Layer) {
+        self.layer = layer;
+    }
+
+    fn get_component(&self) -> Result<&Layer, &'static str> {
+        Ok(&self.layer)
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Layer, &'static str> {
+        Ok(&mut self.layer)
+    }
+}
+
+impl HasComponent<Material> for Cube {
+    fn attach_component(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    fn get_component(&self) -> Result<&Material, &'static str> {
+        Ok(&self.material)
+    }
+
+    fn get_component_mut(&mut self) -> Result<&mut Material, &'static str> {
+        Ok(&mut self.material)
+    }
 }
\ No newline at end of file
@@ -1,2 +1,384 @@
+use js_sys::Array;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+
+/// Buttons of the W3C standard gamepad mapping, in index order.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StandardButton {
+    SOUTH,
+    EAST,
+    WEST,
+    NORTH,
+    LEFT_BUMPER,
+    RIGHT_BUMPER,
+    LEFT_TRIGGER,
+    RIGHT_TRIGGER,
+    SELECT,
+    START,
+    LEFT_STICK,
+    RIGHT_STICK,
+    DPAD_UP,
+    DPAD_DOWN,
+    DPAD_LEFT,
+    DPAD_RIGHT,
+    HOME,
+}
+
+impl StandardButton {
+    pub fn get_index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Axes of the W3C standard gamepad mapping, `Y` is positive downwards.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StandardAxis {
+    LEFT_X,
+    LEFT_Y,
+    RIGHT_X,
+    RIGHT_Y,
+}
+
+impl StandardAxis {
+    pub fn get_index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    CONNECTED(usize),
+    DISCONNECTED(usize),
+}
+
+/// Raw state of a connected gamepad for a single frame, as polled from the browser or replayed.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GamepadFrame {
+    pub index: usize,
+    pub id: String,
+    pub standard: bool,
+    pub buttons: Vec<f32>,
+    pub axes: Vec<f32>,
+}
+
+impl GamepadFrame {
+    fn from_gamepad(gamepad: &web_sys::Gamepad) -> Self {
+        GamepadFrame {
+            index: gamepad.index() as usize,
+            id: gamepad.id(),
+            standard: gamepad.mapping() == web_sys::GamepadMappingType::Standard,
+            buttons: gamepad
+                .buttons()
+                .iter()
+                .map(|button| button.unchecked_into::<web_sys::GamepadButton>().value() as f32)
+                .collect(),
+            axes: gamepad
+                .axes()
+                .iter()
+                .map(|axis| axis.as_f64().unwrap_or(0.) as f32)
+                .collect(),
+        }
+    }
+}
+
 #[derive(Default, Clone, Debug)]
-pub struct Gamepad {}
+pub struct Gamepad {
+    index: usize,
+    id: String,
+    standard: bool,
+    buttons: Vec<f32>,
+    previous_buttons: Vec<f32>,
+    axes: Vec<f32>,
+}
+
+impl Gamepad {
+    const PRESSED: f32 = 0.5;
+
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the browser maps the controller to the standard layout,
+    /// otherwise button and axis names may not match.
+    pub fn is_standard(&self) -> bool {
+        self.standard
+    }
+
+    pub fn get_button(&self, button: StandardButton) -> f32 {
+        self.buttons.get(button.get_index()).copied().unwrap_or(0.)
+    }
+
+    pub fn is_button_down(&self, button: StandardButton) -> bool {
+        self.get_button(button) > Gamepad::PRESSED
+    }
+
+    /// Button went down this frame.
+    pub fn is_button_pressed(&self, button: StandardButton) -> bool {
+        let previous = self.previous_buttons.get(button.get_index()).copied().unwrap_or(0.);
+
+        self.is_button_down(button) && previous <= Gamepad::PRESSED
+    }
+
+    /// Button went up this frame.
+    pub fn is_button_released(&self, button: StandardButton) -> bool {
+        let previous = self.previous_buttons.get(button.get_index()).copied().unwrap_or(0.);
+
+        !self.is_button_down(button) && previous > Gamepad::PRESSED
+    }
+
+    /// Analog trigger value from 0 to 1.
+    pub fn get_trigger(&self, button: StandardButton) -> f32 {
+        self.get_button(button)
+    }
+
+    /// Stick axis with the deadzone already applied.
+    pub fn get_axis(&self, axis: StandardAxis) -> f32 {
+        self.axes.get(axis.get_index()).copied().unwrap_or(0.)
+    }
+
+    pub fn get_left_stick(&self) -> [f32; 2] {
+        [self.get_axis(StandardAxis::LEFT_X), self.get_axis(StandardAxis::LEFT_Y)]
+    }
+
+    pub fn get_right_stick(&self) -> [f32; 2] {
+        [self.get_axis(StandardAxis::RIGHT_X), self.get_axis(StandardAxis::RIGHT_Y)]
+    }
+
+    fn update(&mut self, frame: &GamepadFrame, deadzone: f32) {
+        self.id = frame.id.clone();
+        self.standard = frame.standard;
+
+        self.previous_buttons = std::mem::replace(&mut self.buttons, frame.buttons.clone());
+
+        // Sticks are pairs of axes, the deadzone is radial so diagonals stay smooth.
+        self.axes = frame
+            .axes
+            .chunks(2)
+            .flat_map(|stick| {
+                let x = stick[0];
+                let y = stick.get(1).copied().unwrap_or(0.);
+
+                let [x, y] = apply_deadzone([x, y], deadzone);
+
+                vec![x, y].into_iter().take(stick.len())
+            })
+            .collect();
+    }
+}
+
+/// Rescales a stick so values start at 0 past the deadzone and still reach 1.
+fn apply_deadzone(stick: [f32; 2], deadzone: f32) -> [f32; 2] {
+    let magnitude = (stick[0] * stick[0] + stick[1] * stick[1]).sqrt();
+
+    if magnitude <= deadzone || deadzone >= 1. {
+        return [0., 0.];
+    }
+
+    let scale = ((magnitude - deadzone) / (1. - deadzone)).min(1.) / magnitude;
+
+    [stick[0] * scale, stick[1] * scale]
+}
+
+#[derive(Debug)]
+struct GamepadsState {
+    gamepads: Vec<Option<Gamepad>>,
+    events: Vec<GamepadEvent>,
+    deadzone: f32,
+}
+
+impl Default for GamepadsState {
+    fn default() -> Self {
+        GamepadsState {
+            gamepads: vec![],
+            events: vec![],
+            deadzone: 0.15,
+        }
+    }
+}
+
+/// Gamepads connected to the browser, polled from `navigator.getGamepads()` each frame.
+#[derive(Default, Clone, Debug)]
+pub struct Gamepads {
+    state: Rc<RefCell<GamepadsState>>,
+}
+
+impl Gamepads {
+    /// Reads every connected gamepad from the browser.
+    pub fn poll(&self) -> Vec<GamepadFrame> {
+        let navigator = web_sys::window().unwrap().navigator();
+
+        let polled = match navigator.get_gamepads() {
+            Ok(polled) => polled,
+            Err(_) => Array::new(),
+        };
+
+        polled
+            .iter()
+            .filter_map(|gamepad| gamepad.dyn_into::<web_sys::Gamepad>().ok())
+            .filter(|gamepad| gamepad.connected())
+            .map(|gamepad| GamepadFrame::from_gamepad(&gamepad))
+            .collect()
+    }
+
+    /// Makes `frames` the connected gamepads, called once at the start of `Engine::update`.
+    pub fn apply(&self, frames: &[GamepadFrame]) {
+        let mut state = self.state.borrow_mut();
+
+        let deadzone = state.deadzone;
+
+        state.events.clear();
+
+        let length = frames
+            .iter()
+            .map(|frame| frame.index + 1)
+            .max()
+            .unwrap_or(0)
+            .max(state.gamepads.len());
+
+        state.gamepads.resize(length, None);
+
+        for index in 0..length {
+            let frame = frames.iter().find(|frame| frame.index == index);
+
+            match (frame, state.gamepads[index].is_some()) {
+                (Some(frame), was_connected) => {
+                    if !was_connected {
+                        state.gamepads[index] = Some(Gamepad {
+                            index,
+                            ..Gamepad::default()
+                        });
+
+                        state.events.push(GamepadEvent::CONNECTED(index));
+                    }
+
+                    state.gamepads[index].as_mut().unwrap().update(frame, deadzone);
+                }
+                (None, true) => {
+                    state.gamepads[index] = None;
+
+                    state.events.push(GamepadEvent::DISCONNECTED(index));
+                }
+                (None, false) => {}
+            }
+        }
+    }
+
+    pub fn set_deadzone(&self, deadzone: f32) {
+        self.state.borrow_mut().deadzone = deadzone;
+    }
+
+    pub fn get_deadzone(&self) -> f32 {
+        self.state.borrow().deadzone
+    }
+
+    pub fn get(&self, index: usize) -> Option<Gamepad> {
+        self.state.borrow().gamepads.get(index).cloned().flatten()
+    }
+
+    pub fn get_connected(&self) -> Vec<Gamepad> {
+        self.state.borrow().gamepads.iter().flatten().cloned().collect()
+    }
+
+    /// Connections and disconnections detected during the last frame.
+    pub fn get_events(&self) -> Vec<GamepadEvent> {
+        self.state.borrow().events.clone()
+    }
+
+    pub fn is_button_down(&self, button: StandardButton) -> bool {
+        self.state.borrow().gamepads.iter().flatten().any(|gamepad| gamepad.is_button_down(button))
+    }
+
+    pub fn is_button_pressed(&self, button: StandardButton) -> bool {
+        self.state.borrow().gamepads.iter().flatten().any(|gamepad| gamepad.is_button_pressed(button))
+    }
+
+    pub fn get_button(&self, button: StandardButton) -> f32 {
+        self.state
+            .borrow()
+            .gamepads
+            .iter()
+            .flatten()
+            .map(|gamepad| gamepad.get_button(button))
+            .fold(0., f32::max)
+    }
+
+    /// Sum of the axis over every gamepad, clamped to -1 and 1.
+    pub fn get_axis(&self, axis: StandardAxis) -> f32 {
+        self.state
+            .borrow()
+            .gamepads
+            .iter()
+            .flatten()
+            .map(|gamepad| gamepad.get_axis(axis))
+            .sum::<f32>()
+            .clamp(-1., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn length(stick: [f32; 2]) -> f32 {
+        (stick[0] * stick[0] + stick[1] * stick[1]).sqrt()
+    }
+
+    #[test]
+    fn sticks_inside_the_deadzone_are_zero() {
+        assert_eq!(apply_deadzone([0.1, 0.], 0.15), [0., 0.]);
+        assert_eq!(apply_deadzone([0.1, -0.1], 0.15), [0., 0.]);
+        assert_eq!(apply_deadzone([0.15, 0.], 0.15), [0., 0.]);
+        assert_eq!(apply_deadzone([1., 0.], 1.), [0., 0.]);
+    }
+
+    #[test]
+    fn sticks_ramp_up_from_the_deadzone() {
+        let just_past = apply_deadzone([0.1501, 0.], 0.15);
+
+        assert!(just_past[0] > 0. && just_past[0] < 1e-3);
+
+        let half = apply_deadzone([0., 0.575], 0.15);
+
+        assert!((half[1] - 0.5).abs() < 1e-5);
+
+        // The direction of diagonals is kept.
+        let diagonal = apply_deadzone([0.5, 0.5], 0.15);
+
+        assert!((diagonal[0] - diagonal[1]).abs() < 1e-6);
+        assert!((length(diagonal) - (length([0.5, 0.5]) - 0.15) / 0.85).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sticks_are_clamped_at_the_edge() {
+        assert!((apply_deadzone([1., 0.], 0.15)[0] - 1.).abs() < 1e-6);
+        assert!((length(apply_deadzone([-1., -1.], 0.15)) - 1.).abs() < 1e-6);
+        assert!((length(apply_deadzone([0.9, 0.9], 0.)) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn connected_gamepads_apply_the_deadzone() {
+        let gamepads = Gamepads::default();
+
+        gamepads.set_deadzone(0.2);
+        gamepads.apply(&[GamepadFrame {
+            index: 1,
+            axes: vec![0.1, 0.1, 0.6, 0.],
+            ..GamepadFrame::default()
+        }]);
+
+        let gamepad = gamepads.get(1).unwrap();
+
+        assert_eq!(gamepad.get_left_stick(), [0., 0.]);
+        assert!((gamepad.get_axis(StandardAxis::RIGHT_X) - 0.5).abs() < 1e-5);
+        assert_eq!(gamepads.get_events(), vec![GamepadEvent::CONNECTED(1)]);
+        assert!(gamepads.get(0).is_none());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::io::{Gamepads, JoystickAxis, Keyboard, Mouse, MouseButton, StandardAxis, StandardButton, Touch};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MouseAxis {
+    X,
+    Y,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WheelDirection {
+    UP,
+    DOWN,
+}
+
+/// Digital control an action can be bound to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    /// `KeyboardEvent.code()`, e.g. `"KeyW"`, so bindings follow the physical layout.
+    KEY(String),
+    MOUSE(MouseButton),
+    WHEEL(WheelDirection),
+    /// Button of any connected gamepad.
+    GAMEPAD(StandardButton),
+    /// Named on-screen touch button.
+    VIRTUAL(String),
+}
+
+/// Control contributing to the value of an axis.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    DIGITAL {
+        #[serde(default)]
+        positive: Option<Binding>,
+        #[serde(default)]
+        negative: Option<Binding>,
+    },
+    MOUSE {
+        axis: MouseAxis,
+        scale: f32,
+    },
+    WHEEL {
+        scale: f32,
+    },
+    GAMEPAD {
+        axis: StandardAxis,
+        scale: f32,
+    },
+    /// Analog button such as a trigger, from 0 to `scale`.
+    GAMEPAD_BUTTON {
+        button: StandardButton,
+        scale: f32,
+    },
+    /// Named on-screen touch joystick.
+    JOYSTICK {
+        name: String,
+        axis: JoystickAxis,
+        scale: f32,
+    },
+    /// Single finger drag on the canvas, in pixels.
+    DRAG {
+        axis: MouseAxis,
+        scale: f32,
+    },
+}
+
+/// Named actions and axes bound to keyboard, mouse and gamepad controls.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    actions: BTreeMap<String, Vec<Binding>>,
+    #[serde(default)]
+    axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl InputMap {
+    /// The error names the line and column of the problem.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|error| format!("failed to parse input map: {}", error))
+    }
+
+    pub fn to_json(&self) -> Result<String, &'static str> {
+        serde_json::to_string_pretty(self).map_err(|_| "failed to serialize input map")
+    }
+
+    pub fn bind_action(&mut self, action: &str, binding: Binding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind_action(&mut self, action: &str, binding: &Binding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|b| b != binding);
+        }
+    }
+
+    pub fn rebind_action(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.actions.insert(action.to_string(), bindings);
+    }
+
+    pub fn clear_action(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    pub fn get_actions(&self) -> Vec<String> {
+        self.actions.keys().cloned().collect()
+    }
+
+    pub fn get_action_bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        let bindings = self.axes.entry(axis.to_string()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn rebind_axis(&mut self, axis: &str, bindings: Vec<AxisBinding>) {
+        self.axes.insert(axis.to_string(), bindings);
+    }
+
+    pub fn clear_axis(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    pub fn get_axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    pub fn is_action_down(&self, action: &str, keyboard: &Keyboard, mouse: &Mouse, gamepads: &Gamepads, touch: &Touch) -> bool {
+        self.get_action_bindings(action)
+            .iter()
+            .any(|binding| is_binding_down(binding, keyboard, mouse, gamepads, touch))
+    }
+
+    /// Sum of every control bound to the axis.
+    pub fn get_axis(&self, axis: &str, keyboard: &Keyboard, mouse: &Mouse, gamepads: &Gamepads, touch: &Touch) -> f32 {
+        self.get_axis_bindings(axis)
+            .iter()
+            .map(|binding| get_axis_binding_value(binding, keyboard, mouse, gamepads, touch))
+            .sum()
+    }
+}
+
+fn is_binding_down(binding: &Binding, keyboard: &Keyboard, mouse: &Mouse, gamepads: &Gamepads, touch: &Touch) -> bool {
+    match binding {
+        Binding::KEY(code) => keyboard.is_key_down(code),
+        Binding::MOUSE(button) => mouse.is_down(*button),
+        Binding::WHEEL(WheelDirection::UP) => mouse.get_wheel() < 0.,
+        Binding::WHEEL(WheelDirection::DOWN) => mouse.get_wheel() > 0.,
+        Binding::GAMEPAD(button) => gamepads.is_button_down(*button),
+        Binding::VIRTUAL(name) => touch.is_button_down(name),
+    }
+}
+
+fn get_axis_binding_value(binding: &AxisBinding, keyboard: &Keyboard, mouse: &Mouse, gamepads: &Gamepads, touch: &Touch) -> f32 {
+    let is_down = |binding: &Option<Binding>| {
+        binding
+            .as_ref()
+            .is_some_and(|binding| is_binding_down(binding, keyboard, mouse, gamepads, touch))
+    };
+
+    match binding {
+        AxisBinding::DIGITAL { positive, negative } => {
+            let mut value = 0.;
+
+            if is_down(positive) {
+                value += 1.;
+            }
+
+            if is_down(negative) {
+                value -= 1.;
+            }
+
+            value
+        }
+        AxisBinding::MOUSE { axis: MouseAxis::X, scale } => mouse.get_dx() as f32 * scale,
+        AxisBinding::MOUSE { axis: MouseAxis::Y, scale } => mouse.get_dy() as f32 * scale,
+        AxisBinding::WHEEL { scale } => mouse.get_wheel() as f32 * scale,
+        AxisBinding::GAMEPAD { axis, scale } => gamepads.get_axis(*axis) * scale,
+        AxisBinding::GAMEPAD_BUTTON { button, scale } => gamepads.get_button(*button) * scale,
+        AxisBinding::JOYSTICK { name, axis, scale } => touch.get_joystick_axis(name, *axis) * scale,
+        AxisBinding::DRAG { axis: MouseAxis::X, scale } => touch.get_drag()[0] * scale,
+        AxisBinding::DRAG { axis: MouseAxis::Y, scale } => touch.get_drag()[1] * scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{GamepadFrame, KeyboardFrame, MouseFrame};
+
+    const INPUT: &str = include_str!("../../resources/input.json");
+
+    #[derive(Default)]
+    struct Devices {
+        keyboard: Keyboard,
+        mouse: Mouse,
+        gamepads: Gamepads,
+        touch: Touch,
+    }
+
+    impl Devices {
+        fn hold_keys(&self, keys: &[&str]) {
+            self.keyboard.apply(&KeyboardFrame {
+                keys: keys.iter().map(|key| key.to_string()).collect(),
+                ..KeyboardFrame::default()
+            });
+        }
+
+        fn is_action_down(&self, map: &InputMap, action: &str) -> bool {
+            map.is_action_down(action, &self.keyboard, &self.mouse, &self.gamepads, &self.touch)
+        }
+
+        fn get_axis(&self, map: &InputMap, axis: &str) -> f32 {
+            map.get_axis(axis, &self.keyboard, &self.mouse, &self.gamepads, &self.touch)
+        }
+    }
+
+    #[test]
+    fn input_map_parses() {
+        let map = InputMap::from_json(INPUT).unwrap();
+
+        assert_eq!(map.get_actions(), vec!["look"]);
+        assert_eq!(map.get_action_bindings("look"), &[Binding::MOUSE(MouseButton::PRIMARY)]);
+        assert_eq!(map.get_axis_bindings("move_forward").len(), 4);
+        assert_eq!(
+            map.get_axis_bindings("turn_x")[0],
+            AxisBinding::GAMEPAD {
+                axis: StandardAxis::RIGHT_X,
+                scale: 6.
+            }
+        );
+    }
+
+    #[test]
+    fn parse_errors_name_the_position() {
+        let error = InputMap::from_json("{\n  \"actions\": { \"look\": [{ \"MOUSE\": \"THIRD\" }] }\n}").unwrap_err();
+
+        assert!(error.contains("line 2"), "{}", error);
+    }
+
+    #[test]
+    fn axes_sum_their_bindings() {
+        let map = InputMap::from_json(INPUT).unwrap();
+        let devices = Devices::default();
+
+        devices.hold_keys(&["KeyW", "KeyA", "ArrowLeft"]);
+
+        assert_eq!(devices.get_axis(&map, "move_forward"), 1.);
+        assert_eq!(devices.get_axis(&map, "move_right"), -2.);
+
+        devices.gamepads.apply(&[GamepadFrame {
+            axes: vec![0., -1., 0., 0.],
+            ..GamepadFrame::default()
+        }]);
+
+        // The stick is pushed forward, y is positive downwards.
+        assert_eq!(devices.get_axis(&map, "move_forward"), 2.);
+    }
+
+    #[test]
+    fn rebinding_changes_what_is_reported() {
+        let mut map = InputMap::from_json(INPUT).unwrap();
+        let devices = Devices::default();
+
+        devices.mouse.apply(&MouseFrame {
+            buttons: 0b01,
+            ..MouseFrame::default()
+        });
+        devices.hold_keys(&["KeyE", "KeyW"]);
+
+        assert!(devices.is_action_down(&map, "look"));
+
+        map.rebind_action("look", vec![Binding::KEY("KeyQ".to_string())]);
+
+        assert!(!devices.is_action_down(&map, "look"));
+
+        map.bind_action("look", Binding::KEY("KeyE".to_string()));
+
+        assert!(devices.is_action_down(&map, "look"));
+        assert_eq!(map.get_action_bindings("look").len(), 2);
+
+        map.rebind_axis(
+            "move_forward",
+            vec![AxisBinding::DIGITAL {
+                positive: Some(Binding::KEY("KeyI".to_string())),
+                negative: Some(Binding::KEY("KeyK".to_string())),
+            }],
+        );
+
+        assert_eq!(devices.get_axis(&map, "move_forward"), 0.);
+
+        devices.hold_keys(&["KeyK"]);
+
+        assert_eq!(devices.get_axis(&map, "move_forward"), -1.);
+        assert!(!devices.is_action_down(&map, "look"));
+
+        map.clear_action("look");
+
+        assert!(map.get_action_bindings("look").is_empty());
+    }
+
+    #[test]
+    fn rebound_maps_round_trip() {
+        let mut map = InputMap::from_json(INPUT).unwrap();
+
+        map.unbind_action("look", &Binding::MOUSE(MouseButton::PRIMARY));
+        map.bind_action("jump", Binding::GAMEPAD(StandardButton::SOUTH));
+
+        let reloaded = InputMap::from_json(&map.to_json().unwrap()).unwrap();
+
+        assert!(reloaded.get_action_bindings("look").is_empty());
+        assert_eq!(reloaded.get_action_bindings("jump"), &[Binding::GAMEPAD(StandardButton::SOUTH)]);
+        assert_eq!(reloaded.get_axis_bindings("move_up"), map.get_axis_bindings("move_up"));
+    }
+}
@@ -1,52 +1,147 @@
-use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use web_sys::{EventTarget, KeyboardEvent};
+use std::collections::HashSet;
+use std::rc::Rc;
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
+use web_sys::{Event, EventTarget, KeyboardEvent};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    fn from_event(event: &KeyboardEvent) -> Self {
+        Modifiers {
+            shift: event.shift_key(),
+            ctrl: event.ctrl_key(),
+            alt: event.alt_key(),
+            meta: event.meta_key(),
+        }
+    }
+}
+
+/// Text editing event for UI fields, key repeats are included.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TextInput {
+    CHARACTER(char),
+    BACKSPACE,
+    DELETE,
+    ENTER,
+    TAB,
+    LEFT,
+    RIGHT,
+    HOME,
+    END,
+}
+
+impl TextInput {
+    fn from_event(event: &KeyboardEvent) -> Option<Self> {
+        let key = event.key();
+
+        match key.as_str() {
+            "Backspace" => return Some(TextInput::BACKSPACE),
+            "Delete" => return Some(TextInput::DELETE),
+            "Enter" => return Some(TextInput::ENTER),
+            "Tab" => return Some(TextInput::TAB),
+            "ArrowLeft" => return Some(TextInput::LEFT),
+            "ArrowRight" => return Some(TextInput::RIGHT),
+            "Home" => return Some(TextInput::HOME),
+            "End" => return Some(TextInput::END),
+            _ => {}
+        }
+
+        // Shortcuts are not text.
+        if event.ctrl_key() || event.meta_key() {
+            return None;
+        }
+
+        let mut chars = key.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(TextInput::CHARACTER(c)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyboardFrame {
+    pub keys: Vec<String>,
+    /// Edges since the previous frame, a tap within one frame is in both.
+    pub pressed: Vec<String>,
+    pub released: Vec<String>,
+    pub modifiers: Modifiers,
+    pub text: Vec<TextInput>,
+}
 
-pub enum KeyboardKey {
-    LEFT, FORWARD, BACKWARD, RIGHT, UP, DOWN
+#[derive(Default, Debug)]
+struct KeyboardState {
+    down: HashSet<String>,
+    pressed: HashSet<String>,
+    released: HashSet<String>,
+    modifiers: Modifiers,
+    text: Vec<TextInput>,
+    current: HashSet<String>,
+    frame_pressed: HashSet<String>,
+    frame_released: HashSet<String>,
+    frame_modifiers: Modifiers,
+    frame_text: Vec<TextInput>,
 }
 
-#[derive(Clone, Debug)]
+impl KeyboardState {
+    fn key_down(&mut self, code: String, modifiers: Modifiers) {
+        // Repeats of a held key are not presses.
+        if self.down.insert(code.clone()) {
+            self.pressed.insert(code);
+        }
+
+        self.modifiers = modifiers;
+    }
+
+    fn key_up(&mut self, code: String, modifiers: Modifiers) {
+        if self.down.remove(&code) {
+            self.released.insert(code);
+        }
+
+        self.modifiers = modifiers;
+    }
+
+    fn release_all(&mut self) {
+        let down = std::mem::take(&mut self.down);
+
+        self.released.extend(down);
+        self.modifiers = Modifiers::default();
+    }
+}
+
+/// Keys keyed by `KeyboardEvent.code()` (physical position, e.g. `"KeyW"`).
+#[derive(Default, Clone, Debug)]
 pub struct Keyboard {
-    left: Rc<RefCell<bool>>,
-    right: Rc<RefCell<bool>>,
-    up: Rc<RefCell<bool>>,
-    down: Rc<RefCell<bool>>,
-    forward: Rc<RefCell<bool>>,
-    backward: Rc<RefCell<bool>>
+    state: Rc<RefCell<KeyboardState>>,
 }
 
-impl Default for Keyboard {
-    fn default() -> Self {
+impl Keyboard {
+    pub fn new() -> Self {
         let window_target: EventTarget = web_sys::window().unwrap().into();
 
-        let left = Rc::new(RefCell::new(false));
-        let right = Rc::new(RefCell::new(false));
-        let up = Rc::new(RefCell::new(false));
-        let down = Rc::new(RefCell::new(false));
-        let forward = Rc::new(RefCell::new(false));
-        let backward = Rc::new(RefCell::new(false));
+        let state = Rc::new(RefCell::new(KeyboardState::default()));
 
         {
-            let left = left.clone();
-            let right = right.clone();
-            let up = up.clone();
-            let down = down.clone();
-            let forward = forward.clone();
-            let backward = backward.clone();
+            let state = state.clone();
 
             let key_cb = Closure::wrap(Box::new(move |event: KeyboardEvent| {
-                match event.key().as_str() {
-                    "a" | "A" => *left.clone().borrow_mut() = true,
-                    "s" | "S" => *backward.clone().borrow_mut() = true,
-                    "d" | "D" => *right.clone().borrow_mut() = true,
-                    "w" | "W" => *forward.clone().borrow_mut() = true,
-                    " " => *up.clone().borrow_mut() = true,
-                    "Shift" => *down.clone().borrow_mut() = true,
-                    _ => {}
-                };
+                let mut state = state.borrow_mut();
+
+                state.key_down(event.code(), Modifiers::from_event(&event));
+
+                if let Some(text) = TextInput::from_event(&event) {
+                    state.text.push(text);
+                }
             }) as Box<dyn FnMut(KeyboardEvent)>);
 
             window_target
@@ -57,23 +152,10 @@ impl Default for Keyboard {
         }
 
         {
-            let left = left.clone();
-            let right = right.clone();
-            let up = up.clone();
-            let down = down.clone();
-            let forward = forward.clone();
-            let backward = backward.clone();
+            let state = state.clone();
 
             let key_cb = Closure::wrap(Box::new(move |event: KeyboardEvent| {
-                match event.key().as_str() {
-                    "a" | "A" => *left.clone().borrow_mut() = false,
-                    "s" | "S" => *backward.clone().borrow_mut() = false,
-                    "d" | "D" => *right.clone().borrow_mut() = false,
-                    "w" | "W" => *forward.clone().borrow_mut() = false,
-                    " " => *up.clone().borrow_mut() = false,
-                    "Shift" => *down.clone().borrow_mut() = false,
-                    _ => {}
-                };
+                state.borrow_mut().key_up(event.code(), Modifiers::from_event(&event));
             }) as Box<dyn FnMut(KeyboardEvent)>);
 
             window_target
@@ -83,26 +165,185 @@ impl Default for Keyboard {
             key_cb.forget();
         }
 
-        Keyboard {
-            left,
-            right,
-            up,
-            down,
-            forward,
-            backward
+        {
+            let state = state.clone();
+
+            // Key releases are lost while the window is not focused.
+            let blur_cb = Closure::wrap(Box::new(move |_event: Event| {
+                state.borrow_mut().release_all();
+            }) as Box<dyn FnMut(Event)>);
+
+            window_target
+                .add_event_listener_with_callback("blur", blur_cb.as_ref().unchecked_ref())
+                .unwrap();
+
+            blur_cb.forget();
         }
+
+        Keyboard { state }
+    }
+
+    pub fn poll(&self) -> KeyboardFrame {
+        let mut state = self.state.borrow_mut();
+
+        let sorted = |keys: &HashSet<String>| {
+            let mut keys: Vec<String> = keys.iter().cloned().collect();
+
+            keys.sort();
+
+            keys
+        };
+
+        let frame = KeyboardFrame {
+            keys: sorted(&state.down),
+            pressed: sorted(&state.pressed),
+            released: sorted(&state.released),
+            modifiers: state.modifiers,
+            text: std::mem::take(&mut state.text),
+        };
+
+        state.pressed.clear();
+        state.released.clear();
+
+        frame
+    }
+
+    /// Called once at the start of `Engine::update`.
+    pub fn apply(&self, frame: &KeyboardFrame) {
+        let mut state = self.state.borrow_mut();
+
+        let current: HashSet<String> = frame.keys.iter().cloned().collect();
+
+        // Edges are also derived from the held keys, for frames recorded without them.
+        let pressed = current.difference(&state.current).chain(frame.pressed.iter()).cloned().collect();
+        let released = state.current.difference(&current).chain(frame.released.iter()).cloned().collect();
+
+        state.frame_pressed = pressed;
+        state.frame_released = released;
+        state.current = current;
+
+        state.frame_modifiers = frame.modifiers;
+        state.frame_text = frame.text.clone();
+    }
+
+    pub fn is_key_down(&self, code: &str) -> bool {
+        self.state.borrow().current.contains(code)
+    }
+
+    pub fn is_key_up(&self, code: &str) -> bool {
+        !self.is_key_down(code)
+    }
+
+    pub fn is_key_pressed(&self, code: &str) -> bool {
+        self.state.borrow().frame_pressed.contains(code)
+    }
+
+    pub fn is_key_released(&self, code: &str) -> bool {
+        self.state.borrow().frame_released.contains(code)
+    }
+
+    pub fn get_keys_down(&self) -> Vec<String> {
+        self.state.borrow().current.iter().cloned().collect()
+    }
+
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.state.borrow().frame_modifiers
+    }
+
+    pub fn is_shift_down(&self) -> bool {
+        self.get_modifiers().shift
+    }
+
+    pub fn is_ctrl_down(&self) -> bool {
+        self.get_modifiers().ctrl
+    }
+
+    pub fn is_alt_down(&self) -> bool {
+        self.get_modifiers().alt
+    }
+
+    pub fn is_meta_down(&self) -> bool {
+        self.get_modifiers().meta
+    }
+
+    pub fn get_text(&self) -> Vec<TextInput> {
+        self.state.borrow().frame_text.clone()
     }
 }
 
-impl Keyboard {
-    pub fn is_down(&self, key: KeyboardKey) -> bool {
-        match key {
-            KeyboardKey::UP => self.up.borrow().clone(),
-            KeyboardKey::DOWN => self.down.borrow().clone(),
-            KeyboardKey::RIGHT => self.right.borrow().clone(),
-            KeyboardKey::LEFT => self.left.borrow().clone(),
-            KeyboardKey::FORWARD => self.forward.borrow().clone(),
-            KeyboardKey::BACKWARD => self.backward.borrow().clone()
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keyboard: &Keyboard, code: &str) {
+        keyboard.state.borrow_mut().key_down(code.to_string(), Modifiers::default());
+    }
+
+    fn release(keyboard: &Keyboard, code: &str) {
+        keyboard.state.borrow_mut().key_up(code.to_string(), Modifiers::default());
+    }
+
+    fn next_frame(keyboard: &Keyboard) {
+        let frame = keyboard.poll();
+
+        keyboard.apply(&frame);
+    }
+
+    #[test]
+    fn held_key_is_pressed_once() {
+        let keyboard = Keyboard::default();
+
+        press(&keyboard, "KeyW");
+        next_frame(&keyboard);
+
+        assert!(keyboard.is_key_down("KeyW"));
+        assert!(keyboard.is_key_pressed("KeyW"));
+
+        // Key repeat.
+        press(&keyboard, "KeyW");
+        next_frame(&keyboard);
+
+        assert!(keyboard.is_key_down("KeyW"));
+        assert!(!keyboard.is_key_pressed("KeyW"));
+
+        release(&keyboard, "KeyW");
+        next_frame(&keyboard);
+
+        assert!(keyboard.is_key_up("KeyW"));
+        assert!(keyboard.is_key_released("KeyW"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn tap_inside_one_frame_is_pressed_and_released() {
+        let keyboard = Keyboard::default();
+
+        press(&keyboard, "Space");
+        release(&keyboard, "Space");
+        next_frame(&keyboard);
+
+        assert!(keyboard.is_key_up("Space"));
+        assert!(keyboard.is_key_pressed("Space"));
+        assert!(keyboard.is_key_released("Space"));
+
+        next_frame(&keyboard);
+
+        assert!(!keyboard.is_key_pressed("Space"));
+        assert!(!keyboard.is_key_released("Space"));
+    }
+
+    #[test]
+    fn edges_are_derived_from_frames_without_them() {
+        let keyboard = Keyboard::default();
+
+        keyboard.apply(&KeyboardFrame {
+            keys: vec!["KeyA".to_string()],
+            ..KeyboardFrame::default()
+        });
+
+        assert!(keyboard.is_key_pressed("KeyA"));
+
+        keyboard.apply(&KeyboardFrame::default());
+
+        assert!(keyboard.is_key_released("KeyA"));
+    }
+}
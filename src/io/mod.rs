@@ -1,7 +1,13 @@
 mod keyboard;
 mod mouse;
 mod gamepad;
+mod input_map;
+mod touch;
+mod source;
 
 pub use keyboard::*;
 pub use mouse::*;
-pub use gamepad::*;
\ No newline at end of file
+pub use gamepad::*;
+pub use input_map::*;
+pub use touch::*;
+pub use source::*;
\ No newline at end of file
@@ -1,9 +1,11 @@
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
-use web_sys::{EventTarget, HtmlCanvasElement, MouseEvent};
+use web_sys::{Event, EventTarget, HtmlCanvasElement, MouseEvent, WheelEvent};
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MouseButton {
     PRIMARY,
     SECONDARY,
@@ -12,26 +14,82 @@ pub enum MouseButton {
     FIFTH,
 }
 
+impl MouseButton {
+    fn get_mask(&self) -> u16 {
+        match self {
+            MouseButton::PRIMARY => 0b0000_0001,
+            MouseButton::SECONDARY => 0b0000_0010,
+            MouseButton::AUXILIARY => 0b0000_0100,
+            MouseButton::FORTH => 0b0000_1000,
+            MouseButton::FIFTH => 0b0001_0000,
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MouseFrame {
+    pub buttons: u16,
+    /// Edges since the previous frame, a click within one frame is in both.
+    pub pressed: u16,
+    pub released: u16,
+    pub dx: i32,
+    pub dy: i32,
+    pub wheel: f64,
+    pub x: i32,
+    pub y: i32,
+    pub locked: bool,
+}
+
+#[derive(Default, Debug)]
+struct MouseState {
+    buttons: u16,
+    pressed: u16,
+    released: u16,
+    dx: i32,
+    dy: i32,
+    wheel: f64,
+    x: i32,
+    y: i32,
+    locked: bool,
+    lock_on_click: bool,
+    frame: MouseFrame,
+}
+
+impl MouseState {
+    fn set_buttons(&mut self, buttons: u16) {
+        self.pressed |= buttons & !self.buttons;
+        self.released |= self.buttons & !buttons;
+        self.buttons = buttons;
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct Mouse {
-    buttons: Rc<RefCell<u16>>,
-    dx: Rc<RefCell<i32>>,
-    dy: Rc<RefCell<i32>>,
+    canvas: Option<HtmlCanvasElement>,
+    state: Rc<RefCell<MouseState>>,
 }
 
 impl Mouse {
     pub fn new(canvas: &HtmlCanvasElement) -> Self {
         let canvas_target: EventTarget = canvas.clone().into();
+        let window_target: EventTarget = web_sys::window().unwrap().into();
+        let document_target: EventTarget = web_sys::window().unwrap().document().unwrap().into();
 
-        let buttons = Rc::new(RefCell::new(0u16));
-        let dx = Rc::new(RefCell::new(0i32));
-        let dy = Rc::new(RefCell::new(0i32));
+        let state = Rc::new(RefCell::new(MouseState::default()));
 
         {
-            let buttons = buttons.clone();
+            let state = state.clone();
+            let canvas = canvas.clone();
 
             let mouse_cb = Closure::wrap(Box::new(move |event: MouseEvent| {
-                *buttons.borrow_mut() = event.buttons();
+                let mut state = state.borrow_mut();
+
+                state.set_buttons(event.buttons());
+
+                // Pointer lock is only granted from a user gesture.
+                if state.lock_on_click && !state.locked {
+                    canvas.request_pointer_lock();
+                }
             }) as Box<dyn FnMut(MouseEvent)>);
 
             canvas_target
@@ -42,13 +100,14 @@ impl Mouse {
         }
 
         {
-            let buttons = buttons.clone();
+            let state = state.clone();
 
+            // Buttons can be released outside of the canvas.
             let mouse_cb = Closure::wrap(Box::new(move |event: MouseEvent| {
-                *buttons.borrow_mut() = event.buttons();
+                state.borrow_mut().set_buttons(event.buttons());
             }) as Box<dyn FnMut(MouseEvent)>);
 
-            canvas_target
+            window_target
                 .add_event_listener_with_callback("mouseup", mouse_cb.as_ref().unchecked_ref())
                 .unwrap();
 
@@ -56,12 +115,19 @@ impl Mouse {
         }
 
         {
-            let dx = dx.clone();
-            let dy = dy.clone();
+            let state = state.clone();
 
             let mouse_cb = Closure::wrap(Box::new(move |event: MouseEvent| {
-                *dx.borrow_mut() = event.movement_x();
-                *dy.borrow_mut() = event.movement_y();
+                let mut state = state.borrow_mut();
+
+                state.dx += event.movement_x();
+                state.dy += event.movement_y();
+
+                // The cursor does not move while the pointer is locked.
+                if !state.locked {
+                    state.x = event.offset_x();
+                    state.y = event.offset_y();
+                }
             }) as Box<dyn FnMut(MouseEvent)>);
 
             canvas_target
@@ -72,47 +138,198 @@ impl Mouse {
         }
 
         {
-            let buttons = buttons.clone();
-            let dx = dx.clone();
-            let dy = dy.clone();
-
-            let mouse_cb = Closure::wrap(Box::new(move |_event: MouseEvent| {
-                *buttons.borrow_mut() = 0;
-                *dx.borrow_mut() = 0;
-                *dy.borrow_mut() = 0;
-            }) as Box<dyn FnMut(MouseEvent)>);
+            let state = state.clone();
+
+            let wheel_cb = Closure::wrap(Box::new(move |event: WheelEvent| {
+                event.prevent_default();
+
+                state.borrow_mut().wheel += event.delta_y();
+            }) as Box<dyn FnMut(WheelEvent)>);
 
             canvas_target
-                .add_event_listener_with_callback("mouseleave", mouse_cb.as_ref().unchecked_ref())
+                .add_event_listener_with_callback("wheel", wheel_cb.as_ref().unchecked_ref())
                 .unwrap();
 
-            mouse_cb.forget();
+            wheel_cb.forget();
         }
 
-        Mouse { buttons, dx, dy }
+        {
+            let state = state.clone();
+            let canvas = canvas.clone();
+
+            let lock_cb = Closure::wrap(Box::new(move |_event: Event| {
+                let document = web_sys::window().unwrap().document().unwrap();
+
+                let locked = document
+                    .pointer_lock_element()
+                    .is_some_and(|element| element == **canvas);
+
+                state.borrow_mut().locked = locked;
+            }) as Box<dyn FnMut(Event)>);
+
+            document_target
+                .add_event_listener_with_callback("pointerlockchange", lock_cb.as_ref().unchecked_ref())
+                .unwrap();
+
+            lock_cb.forget();
+        }
+
+        Mouse {
+            canvas: Some(canvas.clone()),
+            state,
+        }
     }
 
-    pub fn is_down(&self, mouse_button: MouseButton) -> bool {
-        let bv = self.buttons.borrow().clone();
-
-        match mouse_button {
-            MouseButton::PRIMARY => bv & 0b0000_0001 == 1,
-            MouseButton::SECONDARY => bv & 0b0000_0010 == 1,
-            MouseButton::AUXILIARY => bv & 0b0000_0100 == 1,
-            MouseButton::FORTH => bv & 0b0000_1000 == 1,
-            MouseButton::FIFTH => bv & 0b0001_0000 == 1,
+    pub fn poll(&self) -> MouseFrame {
+        let mut state = self.state.borrow_mut();
+
+        MouseFrame {
+            buttons: state.buttons,
+            pressed: std::mem::take(&mut state.pressed),
+            released: std::mem::take(&mut state.released),
+            dx: std::mem::take(&mut state.dx),
+            dy: std::mem::take(&mut state.dy),
+            wheel: std::mem::take(&mut state.wheel),
+            x: state.x,
+            y: state.y,
+            locked: state.locked,
         }
     }
 
+    /// Called once at the start of `Engine::update`.
+    pub fn apply(&self, frame: &MouseFrame) {
+        let mut state = self.state.borrow_mut();
+
+        let previous = state.frame.buttons;
+
+        state.frame = frame.clone();
+
+        // Edges are also derived from the held buttons, for frames recorded without them.
+        state.frame.pressed |= frame.buttons & !previous;
+        state.frame.released |= previous & !frame.buttons;
+    }
+
+    pub fn is_down(&self, mouse_button: MouseButton) -> bool {
+        self.state.borrow().frame.buttons & mouse_button.get_mask() != 0
+    }
+
     pub fn is_up(&self, mouse_button: MouseButton) -> bool {
         !self.is_down(mouse_button)
     }
 
+    pub fn is_pressed(&self, mouse_button: MouseButton) -> bool {
+        self.state.borrow().frame.pressed & mouse_button.get_mask() != 0
+    }
+
+    pub fn is_released(&self, mouse_button: MouseButton) -> bool {
+        self.state.borrow().frame.released & mouse_button.get_mask() != 0
+    }
+
+    /// Also reported while the pointer is locked.
     pub fn get_dx(&self) -> i32 {
-        self.dx.borrow().clone()
+        self.state.borrow().frame.dx
     }
 
     pub fn get_dy(&self) -> i32 {
-        self.dy.borrow().clone()
+        self.state.borrow().frame.dy
+    }
+
+    /// CSS pixels from the top left corner of the canvas.
+    pub fn get_position(&self) -> (i32, i32) {
+        let state = self.state.borrow();
+
+        (state.frame.x, state.frame.y)
+    }
+
+    /// Position in pixels of the drawing buffer, which differ from CSS pixels once the canvas is scaled by the page.
+    pub fn get_canvas_position(&self) -> (f32, f32) {
+        let (x, y) = self.get_position();
+
+        let (scale_x, scale_y) = match self.canvas.as_ref() {
+            Some(canvas) if canvas.client_width() > 0 && canvas.client_height() > 0 => (
+                canvas.width() as f32 / canvas.client_width() as f32,
+                canvas.height() as f32 / canvas.client_height() as f32,
+            ),
+            _ => (1., 1.),
+        };
+
+        (x as f32 * scale_x, y as f32 * scale_y)
+    }
+
+    /// Positive when scrolling down.
+    pub fn get_wheel(&self) -> f64 {
+        self.state.borrow().frame.wheel
+    }
+
+    /// Browsers only grant pointer lock from a user gesture such as a click.
+    pub fn set_lock_on_click(&self, lock_on_click: bool) {
+        self.state.borrow_mut().lock_on_click = lock_on_click;
+    }
+
+    pub fn request_pointer_lock(&self) {
+        if let Some(canvas) = self.canvas.as_ref() {
+            canvas.request_pointer_lock();
+        }
+    }
+
+    pub fn exit_pointer_lock(&self) {
+        if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+            document.exit_pointer_lock();
+        }
+    }
+
+    pub fn is_pointer_locked(&self) -> bool {
+        self.state.borrow().frame.locked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_frame(mouse: &Mouse) {
+        let frame = mouse.poll();
+
+        mouse.apply(&frame);
+    }
+
+    #[test]
+    fn held_button_is_pressed_once() {
+        let mouse = Mouse::default();
+
+        mouse.state.borrow_mut().set_buttons(0b01);
+        next_frame(&mouse);
+
+        assert!(mouse.is_down(MouseButton::PRIMARY));
+        assert!(mouse.is_pressed(MouseButton::PRIMARY));
+
+        next_frame(&mouse);
+
+        assert!(mouse.is_down(MouseButton::PRIMARY));
+        assert!(!mouse.is_pressed(MouseButton::PRIMARY));
+
+        mouse.state.borrow_mut().set_buttons(0);
+        next_frame(&mouse);
+
+        assert!(mouse.is_released(MouseButton::PRIMARY));
+    }
+
+    #[test]
+    fn click_inside_one_frame_is_pressed_and_released() {
+        let mouse = Mouse::default();
+
+        mouse.state.borrow_mut().set_buttons(0b10);
+        mouse.state.borrow_mut().set_buttons(0);
+        next_frame(&mouse);
+
+        assert!(mouse.is_up(MouseButton::SECONDARY));
+        assert!(mouse.is_pressed(MouseButton::SECONDARY));
+        assert!(mouse.is_released(MouseButton::SECONDARY));
+        assert!(!mouse.is_pressed(MouseButton::PRIMARY));
+
+        next_frame(&mouse);
+
+        assert!(!mouse.is_pressed(MouseButton::SECONDARY));
+        assert!(!mouse.is_released(MouseButton::SECONDARY));
     }
 }
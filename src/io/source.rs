@@ -0,0 +1,238 @@
+use crate::component::Input;
+use crate::io::{GamepadFrame, KeyboardFrame, MouseFrame, TouchFrame};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Milliseconds the url of a downloaded recording stays valid, browsers may read it after `click` returns.
+const DOWNLOAD_URL_LIFETIME: i32 = 40_000;
+
+/// Everything the devices report for one `Engine::update`, with the dt it ran with.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub dt: f32,
+    pub keyboard: KeyboardFrame,
+    pub mouse: MouseFrame,
+    pub gamepads: Vec<GamepadFrame>,
+    pub touch: TouchFrame,
+}
+
+/// Provides the frame applied to the devices of an `Input` at the start of every update.
+pub trait InputSource: Debug {
+    /// Frame for an update that measured `dt`, `None` once the source is exhausted.
+    fn next_frame(&mut self, input: &Input, dt: f32) -> Option<InputFrame>;
+}
+
+/// Live browser devices.
+#[derive(Default, Clone, Debug)]
+pub struct DomSource {}
+
+impl InputSource for DomSource {
+    fn next_frame(&mut self, input: &Input, dt: f32) -> Option<InputFrame> {
+        Some(InputFrame {
+            dt,
+            keyboard: input.get_keyboard().poll(),
+            mouse: input.get_mouse().poll(),
+            gamepads: input.get_gamepads().poll(),
+            touch: input.get_touch().poll(),
+        })
+    }
+}
+
+/// Frames recorded from an input, shared with the recorder filling it.
+#[derive(Default, Clone, Debug)]
+pub struct Recording {
+    frames: Rc<RefCell<Vec<InputFrame>>>,
+}
+
+impl Recording {
+    pub fn from_frames(frames: Vec<InputFrame>) -> Self {
+        Recording {
+            frames: Rc::new(RefCell::new(frames)),
+        }
+    }
+
+    pub fn get_frames(&self) -> Vec<InputFrame> {
+        self.frames.borrow().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.borrow().is_empty()
+    }
+
+    fn push(&self, frame: InputFrame) {
+        self.frames.borrow_mut().push(frame);
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let frames = bincode::deserialize(bytes).map_err(|_| "failed to read recording")?;
+
+        Ok(Recording::from_frames(frames))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        bincode::serialize(&*self.frames.borrow()).map_err(|_| "failed to write recording")
+    }
+
+    /// Saves the recording as a file through the browser.
+    pub fn download(&self, filename: &str) -> Result<(), &'static str> {
+        let bytes = self.to_bytes()?;
+
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes.as_slice()));
+
+        let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(|_| "failed to create blob")?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|_| "failed to create url")?;
+
+        let document = web_sys::window().unwrap().document().unwrap();
+
+        let anchor: web_sys::HtmlAnchorElement = document
+            .create_element("a")
+            .map_err(|_| "failed to create link")?
+            .dyn_into()
+            .map_err(|_| "failed to create link")?;
+
+        anchor.set_href(&url);
+        anchor.set_download(filename);
+        anchor.click();
+
+        let revoke = Closure::once_into_js(move || {
+            web_sys::Url::revoke_object_url(&url).ok();
+        });
+
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), DOWNLOAD_URL_LIFETIME)
+            .map_err(|_| "failed to schedule url revocation")?;
+
+        Ok(())
+    }
+}
+
+/// Passes the frames of another source through while appending them to a recording.
+#[derive(Debug)]
+pub struct Recorder {
+    source: Box<dyn InputSource>,
+    recording: Recording,
+}
+
+impl Recorder {
+    pub fn new(source: Box<dyn InputSource>, recording: &Recording) -> Self {
+        Recorder {
+            source,
+            recording: recording.clone(),
+        }
+    }
+}
+
+impl InputSource for Recorder {
+    fn next_frame(&mut self, input: &Input, dt: f32) -> Option<InputFrame> {
+        let frame = self.source.next_frame(input, dt)?;
+
+        self.recording.push(frame.clone());
+
+        Some(frame)
+    }
+}
+
+/// Plays a recording back, the recorded dt replaces the measured one.
+/// Simulations only match when the replay starts from the state the recording started from.
+#[derive(Debug)]
+pub struct Replay {
+    frames: Vec<InputFrame>,
+    index: usize,
+}
+
+impl Replay {
+    pub fn new(recording: &Recording) -> Self {
+        Replay {
+            frames: recording.get_frames(),
+            index: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.frames.len()
+    }
+
+    fn next_recorded(&mut self) -> Option<InputFrame> {
+        let frame = self.frames.get(self.index).cloned()?;
+
+        self.index += 1;
+
+        Some(frame)
+    }
+}
+
+impl InputSource for Replay {
+    fn next_frame(&mut self, input: &Input, dt: f32) -> Option<InputFrame> {
+        // Live events are dropped so they do not leak into the frames after the replay.
+        DomSource::default().next_frame(input, dt);
+
+        self.next_recorded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source producing scripted frames, standing in for the browser devices.
+    #[derive(Debug)]
+    struct ScriptedSource {
+        frame: u16,
+    }
+
+    impl InputSource for ScriptedSource {
+        fn next_frame(&mut self, _input: &Input, dt: f32) -> Option<InputFrame> {
+            self.frame += 1;
+
+            let mut frame = InputFrame {
+                dt,
+                ..InputFrame::default()
+            };
+
+            frame.keyboard.pressed.push(format!("Key{}", self.frame));
+            frame.mouse.buttons = self.frame;
+
+            Some(frame)
+        }
+    }
+
+    #[test]
+    fn replay_returns_the_recorded_frames() {
+        let input = Input::default();
+        let recording = Recording::default();
+        let mut recorder = Recorder::new(Box::new(ScriptedSource { frame: 0 }), &recording);
+
+        let dts = [16.6, 17.1, 33.4, 8.];
+
+        let recorded: Vec<InputFrame> = dts.iter().map(|&dt| recorder.next_frame(&input, dt).unwrap()).collect();
+
+        assert_eq!(recording.get_frames(), recorded);
+
+        let bytes = recording.to_bytes().unwrap();
+        let mut replay = Replay::new(&Recording::from_bytes(&bytes).unwrap());
+
+        let mut replayed = vec![];
+
+        while !replay.is_finished() {
+            replayed.push(replay.next_recorded().unwrap());
+        }
+
+        assert_eq!(replayed, recorded);
+        assert_eq!(replayed.iter().map(|frame| frame.dt).collect::<Vec<_>>(), dts);
+        assert_eq!(replay.next_recorded(), None);
+    }
+
+    #[test]
+    fn corrupt_recordings_are_rejected() {
+        assert!(Recording::from_bytes(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,791 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlCanvasElement, HtmlElement, TouchEvent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JoystickAxis {
+    X,
+    Y,
+}
+
+/// Finger on the canvas, positions are in CSS pixels from its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub x: f32,
+    pub y: f32,
+    pub dx: f32,
+    pub dy: f32,
+    pub start_x: f32,
+    pub start_y: f32,
+    pub start_time: f64,
+    pub time: f64,
+    /// Held by a virtual control, such touches do not produce gestures.
+    pub captured: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    TAP { x: f32, y: f32 },
+    /// Single finger movement during the frame.
+    DRAG { x: f32, y: f32, dx: f32, dy: f32 },
+    /// Ratio between the distance of two fingers now and on the previous frame.
+    PINCH { scale: f32 },
+    /// Two finger rotation during the frame in radians, clockwise on screen.
+    ROTATE { angle: f32 },
+}
+
+/// On-screen stick placed at a fraction of the canvas, its value is in the unit circle.
+#[derive(Clone, Debug)]
+pub struct VirtualJoystick {
+    name: String,
+    center: [f32; 2],
+    radius: f32,
+    touch: Option<i32>,
+    value: [f32; 2],
+    frame_value: [f32; 2],
+    base: Option<HtmlElement>,
+    knob: Option<HtmlElement>,
+}
+
+impl VirtualJoystick {
+    pub fn new(name: &str, center: [f32; 2], radius: f32) -> Self {
+        VirtualJoystick {
+            name: name.to_string(),
+            center,
+            radius,
+            touch: None,
+            value: [0., 0.],
+            frame_value: [0., 0.],
+            base: create_overlay(radius, "rgba(255, 255, 255, 0.15)"),
+            knob: create_overlay(radius / 2., "rgba(255, 255, 255, 0.4)"),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> [f32; 2] {
+        self.frame_value
+    }
+
+    fn set_touch(&mut self, point: &TouchPoint, rect: &CanvasRect) {
+        let [cx, cy] = rect.get_pixels(self.center);
+
+        let x = (point.x - cx) / self.radius;
+        let y = (point.y - cy) / self.radius;
+
+        let length = (x * x + y * y).sqrt().max(1.);
+
+        self.value = [x / length, y / length];
+    }
+
+    fn update_overlay(&self, rect: &CanvasRect) {
+        let [cx, cy] = rect.get_pixels(self.center);
+
+        place_overlay(self.base.as_ref(), rect, [cx, cy], self.radius);
+        place_overlay(
+            self.knob.as_ref(),
+            rect,
+            [cx + self.frame_value[0] * self.radius, cy + self.frame_value[1] * self.radius],
+            self.radius / 2.,
+        );
+    }
+}
+
+/// On-screen round button placed at a fraction of the canvas.
+#[derive(Clone, Debug)]
+pub struct VirtualButton {
+    name: String,
+    center: [f32; 2],
+    radius: f32,
+    touch: Option<i32>,
+    frame_down: bool,
+    overlay: Option<HtmlElement>,
+}
+
+impl VirtualButton {
+    pub fn new(name: &str, center: [f32; 2], radius: f32) -> Self {
+        VirtualButton {
+            name: name.to_string(),
+            center,
+            radius,
+            touch: None,
+            frame_down: false,
+            overlay: create_overlay(radius, "rgba(255, 255, 255, 0.25)"),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.frame_down
+    }
+
+    fn update_overlay(&self, rect: &CanvasRect) {
+        place_overlay(self.overlay.as_ref(), rect, rect.get_pixels(self.center), self.radius);
+
+        if let Some(overlay) = self.overlay.as_ref() {
+            let opacity = if self.is_down() { "1" } else { "0.6" };
+
+            overlay.style().set_property("opacity", opacity).unwrap();
+        }
+    }
+}
+
+/// Canvas bounds in CSS pixels of the page.
+#[derive(Default, Clone, Copy, Debug)]
+struct CanvasRect {
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl CanvasRect {
+    fn from_canvas(canvas: &HtmlCanvasElement) -> Self {
+        let rect = canvas.get_bounding_client_rect();
+
+        CanvasRect {
+            left: rect.left() as f32,
+            top: rect.top() as f32,
+            width: rect.width() as f32,
+            height: rect.height() as f32,
+        }
+    }
+
+    fn get_pixels(&self, fraction: [f32; 2]) -> [f32; 2] {
+        [fraction[0] * self.width, fraction[1] * self.height]
+    }
+
+    fn contains(&self, center: [f32; 2], radius: f32, point: &TouchPoint) -> bool {
+        let [cx, cy] = self.get_pixels(center);
+
+        (point.x - cx).powi(2) + (point.y - cy).powi(2) <= radius * radius
+    }
+}
+
+fn create_overlay(radius: f32, color: &str) -> Option<HtmlElement> {
+    let document = web_sys::window()?.document()?;
+
+    let overlay: HtmlElement = document.create_element("div").ok()?.dyn_into().ok()?;
+
+    let style = overlay.style();
+    let size = format!("{}px", radius * 2.);
+
+    style.set_property("position", "fixed").ok()?;
+    style.set_property("width", &size).ok()?;
+    style.set_property("height", &size).ok()?;
+    style.set_property("border-radius", "50%").ok()?;
+    style.set_property("background", color).ok()?;
+    style.set_property("pointer-events", "none").ok()?;
+    style.set_property("display", "none").ok()?;
+
+    document.body()?.append_child(&overlay).ok()?;
+
+    Some(overlay)
+}
+
+fn place_overlay(overlay: Option<&HtmlElement>, rect: &CanvasRect, center: [f32; 2], radius: f32) {
+    if let Some(overlay) = overlay {
+        let style = overlay.style();
+
+        style
+            .set_property("left", &format!("{}px", rect.left + center[0] - radius))
+            .unwrap();
+        style
+            .set_property("top", &format!("{}px", rect.top + center[1] - radius))
+            .unwrap();
+        style.set_property("display", "block").unwrap();
+    }
+}
+
+fn hide_overlay(overlay: Option<&HtmlElement>) {
+    if let Some(overlay) = overlay {
+        overlay.style().set_property("display", "none").unwrap();
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TouchFrame {
+    pub touches: Vec<TouchPoint>,
+    pub ended: Vec<TouchPoint>,
+    pub joysticks: Vec<[f32; 2]>,
+    pub buttons: Vec<bool>,
+}
+
+#[derive(Default, Debug)]
+struct TouchState {
+    live: Vec<TouchPoint>,
+    ended: Vec<TouchPoint>,
+    current: Vec<TouchPoint>,
+    current_ended: Vec<TouchPoint>,
+    previous: Vec<TouchPoint>,
+    gestures: Vec<Gesture>,
+    joysticks: Vec<VirtualJoystick>,
+    buttons: Vec<VirtualButton>,
+    controls_visible: bool,
+}
+
+impl TouchState {
+    fn start(&mut self, mut point: TouchPoint, rect: &CanvasRect) {
+        if let Some(joystick) = self
+            .joysticks
+            .iter_mut()
+            .find(|joystick| joystick.touch.is_none() && rect.contains(joystick.center, joystick.radius, &point))
+        {
+            joystick.touch = Some(point.id);
+            joystick.set_touch(&point, rect);
+
+            point.captured = true;
+        } else if let Some(button) = self
+            .buttons
+            .iter_mut()
+            .find(|button| button.touch.is_none() && rect.contains(button.center, button.radius, &point))
+        {
+            button.touch = Some(point.id);
+
+            point.captured = true;
+        }
+
+        self.live.push(point);
+    }
+
+    fn moved(&mut self, id: i32, x: f32, y: f32, time: f64, rect: &CanvasRect) {
+        let point = match self.live.iter_mut().find(|point| point.id == id) {
+            Some(point) => point,
+            None => return,
+        };
+
+        point.dx += x - point.x;
+        point.dy += y - point.y;
+        point.x = x;
+        point.y = y;
+        point.time = time;
+
+        let point = *point;
+
+        if let Some(joystick) = self.joysticks.iter_mut().find(|joystick| joystick.touch == Some(id)) {
+            joystick.set_touch(&point, rect);
+        }
+    }
+
+    fn end(&mut self, id: i32, time: f64) {
+        for joystick in self.joysticks.iter_mut().filter(|joystick| joystick.touch == Some(id)) {
+            joystick.touch = None;
+            joystick.value = [0., 0.];
+        }
+
+        for button in self.buttons.iter_mut().filter(|button| button.touch == Some(id)) {
+            button.touch = None;
+        }
+
+        if let Some(index) = self.live.iter().position(|point| point.id == id) {
+            let mut point = self.live.remove(index);
+
+            point.time = time;
+
+            self.ended.push(point);
+        }
+    }
+
+    fn recognize(&mut self) {
+        self.gestures.clear();
+
+        const TAP_DISTANCE: f32 = 10.;
+        const TAP_DURATION: f64 = 300.;
+
+        for point in self.current_ended.iter().filter(|point| !point.captured) {
+            let distance = ((point.x - point.start_x).powi(2) + (point.y - point.start_y).powi(2)).sqrt();
+
+            if distance < TAP_DISTANCE && point.time - point.start_time < TAP_DURATION {
+                self.gestures.push(Gesture::TAP { x: point.x, y: point.y });
+            }
+        }
+
+        let free: Vec<&TouchPoint> = self.current.iter().filter(|point| !point.captured).collect();
+
+        match free.as_slice() {
+            [point] if point.dx != 0. || point.dy != 0. => {
+                self.gestures.push(Gesture::DRAG {
+                    x: point.x,
+                    y: point.y,
+                    dx: point.dx,
+                    dy: point.dy,
+                });
+            }
+            [a, b] => {
+                let before = |point: &TouchPoint| [point.x - point.dx, point.y - point.dy];
+
+                let [ax0, ay0] = before(a);
+                let [bx0, by0] = before(b);
+
+                let distance0 = ((bx0 - ax0).powi(2) + (by0 - ay0).powi(2)).sqrt();
+                let distance1 = ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+
+                if distance0 > 0. && distance1 != distance0 {
+                    self.gestures.push(Gesture::PINCH {
+                        scale: distance1 / distance0,
+                    });
+                }
+
+                let angle0 = (by0 - ay0).atan2(bx0 - ax0);
+                let angle1 = (b.y - a.y).atan2(b.x - a.x);
+
+                let mut angle = angle1 - angle0;
+
+                if angle > std::f32::consts::PI {
+                    angle -= 2. * std::f32::consts::PI;
+                } else if angle < -std::f32::consts::PI {
+                    angle += 2. * std::f32::consts::PI;
+                }
+
+                if angle != 0. {
+                    self.gestures.push(Gesture::ROTATE { angle });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Touches on a canvas with gestures and optional virtual controls.
+#[derive(Default, Clone, Debug)]
+pub struct Touch {
+    canvas: Option<HtmlCanvasElement>,
+    state: Rc<RefCell<TouchState>>,
+}
+
+impl Touch {
+    pub fn new(canvas: &HtmlCanvasElement) -> Self {
+        let canvas_target: EventTarget = canvas.clone().into();
+
+        let state = Rc::new(RefCell::new(TouchState::default()));
+
+        {
+            let state = state.clone();
+            let canvas = canvas.clone();
+
+            let touch_cb = Closure::wrap(Box::new(move |event: TouchEvent| {
+                // Keeps the browser from scrolling, zooming and emulating mouse events.
+                event.prevent_default();
+
+                let rect = CanvasRect::from_canvas(&canvas);
+                let touches = event.changed_touches();
+
+                let mut state = state.borrow_mut();
+
+                for i in 0..touches.length() {
+                    if let Some(touch) = touches.get(i) {
+                        let x = touch.client_x() as f32 - rect.left;
+                        let y = touch.client_y() as f32 - rect.top;
+
+                        state.start(
+                            TouchPoint {
+                                id: touch.identifier(),
+                                x,
+                                y,
+                                dx: 0.,
+                                dy: 0.,
+                                start_x: x,
+                                start_y: y,
+                                start_time: event.time_stamp(),
+                                time: event.time_stamp(),
+                                captured: false,
+                            },
+                            &rect,
+                        );
+                    }
+                }
+            }) as Box<dyn FnMut(TouchEvent)>);
+
+            canvas_target
+                .add_event_listener_with_callback("touchstart", touch_cb.as_ref().unchecked_ref())
+                .unwrap();
+
+            touch_cb.forget();
+        }
+
+        {
+            let state = state.clone();
+            let canvas = canvas.clone();
+
+            let touch_cb = Closure::wrap(Box::new(move |event: TouchEvent| {
+                event.prevent_default();
+
+                let rect = CanvasRect::from_canvas(&canvas);
+                let touches = event.changed_touches();
+
+                let mut state = state.borrow_mut();
+
+                for i in 0..touches.length() {
+                    if let Some(touch) = touches.get(i) {
+                        state.moved(
+                            touch.identifier(),
+                            touch.client_x() as f32 - rect.left,
+                            touch.client_y() as f32 - rect.top,
+                            event.time_stamp(),
+                            &rect,
+                        );
+                    }
+                }
+            }) as Box<dyn FnMut(TouchEvent)>);
+
+            canvas_target
+                .add_event_listener_with_callback("touchmove", touch_cb.as_ref().unchecked_ref())
+                .unwrap();
+
+            touch_cb.forget();
+        }
+
+        for event_name in ["touchend", "touchcancel"].iter() {
+            let state = state.clone();
+
+            let touch_cb = Closure::wrap(Box::new(move |event: TouchEvent| {
+                event.prevent_default();
+
+                let touches = event.changed_touches();
+
+                let mut state = state.borrow_mut();
+
+                for i in 0..touches.length() {
+                    if let Some(touch) = touches.get(i) {
+                        state.end(touch.identifier(), event.time_stamp());
+                    }
+                }
+            }) as Box<dyn FnMut(TouchEvent)>);
+
+            canvas_target
+                .add_event_listener_with_callback(event_name, touch_cb.as_ref().unchecked_ref())
+                .unwrap();
+
+            touch_cb.forget();
+        }
+
+        Touch {
+            canvas: Some(canvas.clone()),
+            state,
+        }
+    }
+
+    /// Whether the browser reports a touch screen.
+    pub fn is_supported() -> bool {
+        web_sys::window().is_some_and(|window| window.navigator().max_touch_points() > 0)
+    }
+
+    pub fn poll(&self) -> TouchFrame {
+        let mut state = self.state.borrow_mut();
+
+        let touches = state.live.clone();
+
+        for point in state.live.iter_mut() {
+            point.dx = 0.;
+            point.dy = 0.;
+        }
+
+        TouchFrame {
+            touches,
+            ended: std::mem::take(&mut state.ended),
+            joysticks: state.joysticks.iter().map(|joystick| joystick.value).collect(),
+            buttons: state.buttons.iter().map(|button| button.touch.is_some()).collect(),
+        }
+    }
+
+    /// Called once at the start of `Engine::update`.
+    pub fn apply(&self, frame: &TouchFrame) {
+        let mut state = self.state.borrow_mut();
+
+        state.previous = std::mem::replace(&mut state.current, frame.touches.clone());
+        state.current_ended = frame.ended.clone();
+
+        for (joystick, value) in state.joysticks.iter_mut().zip(frame.joysticks.iter()) {
+            joystick.frame_value = *value;
+        }
+
+        for (button, down) in state.buttons.iter_mut().zip(frame.buttons.iter()) {
+            button.frame_down = *down;
+        }
+
+        state.recognize();
+
+        if let Some(canvas) = self.canvas.as_ref() {
+            let rect = CanvasRect::from_canvas(canvas);
+
+            if state.controls_visible {
+                state.joysticks.iter().for_each(|joystick| joystick.update_overlay(&rect));
+                state.buttons.iter().for_each(|button| button.update_overlay(&rect));
+            }
+        }
+    }
+
+    pub fn get_touches(&self) -> Vec<TouchPoint> {
+        self.state.borrow().current.clone()
+    }
+
+    pub fn get_touch_count(&self) -> usize {
+        self.state.borrow().current.len()
+    }
+
+    /// Whether the touch was already down on the previous frame.
+    pub fn was_touching(&self, id: i32) -> bool {
+        self.state.borrow().previous.iter().any(|point| point.id == id)
+    }
+
+    pub fn get_gestures(&self) -> Vec<Gesture> {
+        self.state.borrow().gestures.clone()
+    }
+
+    /// Sum of the single finger drags of the frame.
+    pub fn get_drag(&self) -> [f32; 2] {
+        self.state
+            .borrow()
+            .gestures
+            .iter()
+            .fold([0., 0.], |drag, gesture| match gesture {
+                Gesture::DRAG { dx, dy, .. } => [drag[0] + dx, drag[1] + dy],
+                _ => drag,
+            })
+    }
+
+    /// Product of the pinches of the frame, 1 when there are none.
+    pub fn get_pinch(&self) -> f32 {
+        self.state
+            .borrow()
+            .gestures
+            .iter()
+            .fold(1., |pinch, gesture| match gesture {
+                Gesture::PINCH { scale } => pinch * scale,
+                _ => pinch,
+            })
+    }
+
+    pub fn add_joystick(&self, joystick: VirtualJoystick) {
+        self.state.borrow_mut().joysticks.push(joystick);
+    }
+
+    pub fn add_button(&self, button: VirtualButton) {
+        self.state.borrow_mut().buttons.push(button);
+    }
+
+    pub fn set_controls_visible(&self, visible: bool) {
+        let mut state = self.state.borrow_mut();
+
+        state.controls_visible = visible;
+
+        if !visible {
+            for joystick in state.joysticks.iter() {
+                hide_overlay(joystick.base.as_ref());
+                hide_overlay(joystick.knob.as_ref());
+            }
+
+            for button in state.buttons.iter() {
+                hide_overlay(button.overlay.as_ref());
+            }
+        }
+    }
+
+    pub fn get_joystick(&self, name: &str) -> [f32; 2] {
+        self.state
+            .borrow()
+            .joysticks
+            .iter()
+            .find(|joystick| joystick.name == name)
+            .map_or([0., 0.], |joystick| joystick.get_value())
+    }
+
+    pub fn get_joystick_axis(&self, name: &str, axis: JoystickAxis) -> f32 {
+        let value = self.get_joystick(name);
+
+        match axis {
+            JoystickAxis::X => value[0],
+            JoystickAxis::Y => value[1],
+        }
+    }
+
+    pub fn is_button_down(&self, name: &str) -> bool {
+        self.state
+            .borrow()
+            .buttons
+            .iter()
+            .any(|button| button.name == name && button.is_down())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn start(touch: &Touch, id: i32, x: f32, y: f32, time: f64) {
+        let point = TouchPoint {
+            id,
+            x,
+            y,
+            dx: 0.,
+            dy: 0.,
+            start_x: x,
+            start_y: y,
+            start_time: time,
+            time,
+            captured: false,
+        };
+
+        touch.state.borrow_mut().start(point, &CanvasRect::default());
+    }
+
+    fn move_to(touch: &Touch, id: i32, x: f32, y: f32, time: f64) {
+        touch.state.borrow_mut().moved(id, x, y, time, &CanvasRect::default());
+    }
+
+    fn end(touch: &Touch, id: i32, time: f64) {
+        touch.state.borrow_mut().end(id, time);
+    }
+
+    fn next_frame(touch: &Touch) -> Vec<Gesture> {
+        let frame = touch.poll();
+
+        touch.apply(&frame);
+
+        touch.get_gestures()
+    }
+
+    fn tap(distance: f32, duration: f64) -> Vec<Gesture> {
+        let touch = Touch::default();
+
+        start(&touch, 0, 100., 100., 1000.);
+        move_to(&touch, 0, 100. + distance, 100., 1000. + duration / 2.);
+        end(&touch, 0, 1000. + duration);
+
+        next_frame(&touch)
+            .into_iter()
+            .filter(|gesture| matches!(gesture, Gesture::TAP { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn short_touches_that_stay_in_place_are_taps() {
+        assert_eq!(tap(0., 50.), vec![Gesture::TAP { x: 100., y: 100. }]);
+        assert_eq!(tap(9.9, 299.), vec![Gesture::TAP { x: 109.9, y: 100. }]);
+
+        assert!(tap(10., 50.).is_empty());
+        assert!(tap(0., 300.).is_empty());
+    }
+
+    #[test]
+    fn single_fingers_drag() {
+        let touch = Touch::default();
+
+        start(&touch, 0, 100., 100., 0.);
+
+        assert!(next_frame(&touch).is_empty());
+
+        move_to(&touch, 0, 102., 99., 16.);
+        move_to(&touch, 0, 105., 97., 24.);
+
+        assert_eq!(
+            next_frame(&touch),
+            vec![Gesture::DRAG {
+                x: 105.,
+                y: 97.,
+                dx: 5.,
+                dy: -3.
+            }]
+        );
+        assert_eq!(touch.get_drag(), [5., -3.]);
+
+        // Deltas are consumed by the frame.
+        assert!(next_frame(&touch).is_empty());
+        assert_eq!(touch.get_drag(), [0., 0.]);
+        assert!(touch.was_touching(0));
+    }
+
+    #[test]
+    fn spreading_two_fingers_pinches() {
+        let touch = Touch::default();
+
+        start(&touch, 0, 100., 100., 0.);
+        start(&touch, 1, 200., 100., 0.);
+        next_frame(&touch);
+
+        move_to(&touch, 1, 300., 100., 16.);
+
+        assert_eq!(next_frame(&touch), vec![Gesture::PINCH { scale: 2. }]);
+        assert_eq!(touch.get_pinch(), 2.);
+
+        move_to(&touch, 0, 150., 100., 32.);
+        move_to(&touch, 1, 250., 100., 32.);
+
+        assert_eq!(next_frame(&touch), vec![Gesture::PINCH { scale: 0.5 }]);
+        assert_eq!(next_frame(&touch), vec![]);
+        assert_eq!(touch.get_pinch(), 1.);
+    }
+
+    #[test]
+    fn turning_two_fingers_rotates_clockwise() {
+        let touch = Touch::default();
+
+        start(&touch, 0, 100., 100., 0.);
+        start(&touch, 1, 200., 100., 0.);
+        next_frame(&touch);
+
+        // Down the screen is clockwise, the distance between the fingers is kept.
+        move_to(&touch, 1, 100., 200., 16.);
+
+        match next_frame(&touch).as_slice() {
+            [Gesture::ROTATE { angle }] => assert!((angle - FRAC_PI_2).abs() < 1e-5),
+            gestures => panic!("unexpected gestures {:?}", gestures),
+        }
+
+        assert_eq!(touch.get_pinch(), 1.);
+    }
+
+    #[test]
+    fn rotations_wrap_around_half_turns() {
+        let touch = Touch::default();
+
+        start(&touch, 0, 0., 0., 0.);
+        start(&touch, 1, -100., 1., 0.);
+        next_frame(&touch);
+
+        move_to(&touch, 1, -100., -1., 16.);
+
+        match next_frame(&touch).as_slice() {
+            [Gesture::ROTATE { angle }] => assert!(*angle > 0. && *angle < 0.03),
+            gestures => panic!("unexpected gestures {:?}", gestures),
+        }
+    }
+
+    #[test]
+    fn captured_touches_make_no_gestures() {
+        let touch = Touch::default();
+
+        touch.add_joystick(VirtualJoystick {
+            name: "move".to_string(),
+            center: [0., 0.],
+            radius: 50.,
+            touch: None,
+            value: [0., 0.],
+            frame_value: [0., 0.],
+            base: None,
+            knob: None,
+        });
+
+        // The canvas rect is empty, so the joystick is centered on the top left corner.
+        start(&touch, 0, 10., 0., 0.);
+        move_to(&touch, 0, 100., 0., 16.);
+
+        assert!(next_frame(&touch).is_empty());
+        assert_eq!(touch.get_joystick("move"), [1., 0.]);
+
+        end(&touch, 0, 32.);
+
+        assert!(next_frame(&touch).is_empty());
+        assert_eq!(touch.get_joystick("move"), [0., 0.]);
+    }
+}
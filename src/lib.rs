@@ -1,3 +1,4 @@
+mod assets;
 mod builder;
 mod component;
 mod controller;
@@ -8,6 +9,7 @@ mod model;
 mod render;
 mod utils;
 
+pub use assets::*;
 pub use builder::*;
 pub use component::*;
 pub use controller::*;
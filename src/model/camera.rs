@@ -1,9 +1,13 @@
 use crate::component::{HasComponents, HasComponent, Transform};
 use crate::controller::HasControllers;
-use crate::render::CameraRenderer;
+use crate::render::{CameraRenderer, Ray};
 use mat4;
+use std::any::Any;
 
 pub trait CameraModel: HasComponents + HasControllers + HasComponent<Transform> {
+    /// Concrete camera, for the registry to find its type when saving a scene.
+    fn as_any(&self) -> &dyn Any;
+
     fn get_renderer(&self) -> &CameraRenderer;
 
     fn get_renderer_mut(&mut self) -> &mut CameraRenderer;
@@ -38,6 +42,10 @@ pub trait CameraModel: HasComponents + HasControllers + HasComponent<Transform>
         matrix
     }
 
+    fn screen_point_to_ray(&mut self, x: f32, y: f32) -> Option<Ray> {
+        self.get_renderer_mut().screen_point_to_ray(x, y)
+    }
+
     fn update_matrix(&mut self) {
         let matrix = self.calculate_camera_matrix().clone();
 
@@ -1,22 +1,101 @@
 use crate::*;
-use web_sys::WebGlProgram;
+use std::any::Any;
+
+pub trait ObjectModel: HasComponents + HasControllers + HasComponent<Transform> + HasComponent<Shader> + HasComponent<Layer> + HasComponent<Material> {
+    /// Concrete object, for the registry to find its type when saving a scene.
+    fn as_any(&self) -> &dyn Any;
 
-pub trait ObjectModel: HasComponents + HasControllers + HasComponent<Transform> + HasComponent<Shader> {
     fn init_renderer(&mut self, gl: &web_sys::WebGl2RenderingContext);
 
     fn render_renderer(&mut self, gl: &web_sys::WebGl2RenderingContext, camera: &mut CameraRenderer);
 
-    fn get_program(&self) -> &WebGlProgram;
+    fn get_program(&self) -> ShaderProgram;
+
+    fn get_local_bounds(&self) -> Bounds;
+
+    fn get_vertices(&self) -> &[f32];
+
+    fn get_indices(&self) -> Indices<'_>;
+
+    fn get_layer_mask(&self) -> u32 {
+        let layer: &Layer = self.get_component().unwrap();
+
+        layer.get_mask()
+    }
+
+    fn get_world_bounds(&self) -> Bounds {
+        let transform: &Transform = self.get_component().unwrap();
+
+        self.get_local_bounds().transform(&transform.get_matrix())
+    }
     
+    fn raycast(&self, ray: &Ray) -> Option<RayIntersection> {
+        ray.intersect_box(&self.get_world_bounds().aabb)?;
+
+        let transform: &Transform = self.get_component().unwrap();
+        let world_matrix = transform.get_matrix();
+        let mut inverse_matrix = mat4::new_identity::<f32>();
+        mat4::inv(&mut inverse_matrix, &world_matrix);
+
+        let local_ray = ray.transform(&inverse_matrix);
+        let vertices = self.get_vertices();
+
+        let vertex = |index: u32| {
+            let i = index as usize * 3;
+
+            [vertices[i], vertices[i + 1], vertices[i + 2]]
+        };
+
+        let mut closest: Option<RayIntersection> = None;
+
+        let indices = self.get_indices();
+
+        for triangle in (0..indices.get_triangle_count()).map(|triangle| indices.get_triangle(triangle)) {
+            let hit = local_ray.intersect_triangle(&vertex(triangle[0]), &vertex(triangle[1]), &vertex(triangle[2]));
+
+            if let Some(hit) = hit {
+                if closest.is_none_or(|c| hit.distance < c.distance) {
+                    closest = Some(hit);
+                }
+            }
+        }
+
+        let mut hit = closest?;
+
+        // Normals go back to world space through the inverse transpose.
+        let n = hit.normal;
+        let mut normal = [0f32; 3];
+
+        for (i, value) in normal.iter_mut().enumerate() {
+            *value = inverse_matrix[i * 4] * n[0] + inverse_matrix[i * 4 + 1] * n[1] + inverse_matrix[i * 4 + 2] * n[2];
+        }
+
+        let normal_copy = normal;
+        vec3::norm(&mut normal, &normal_copy);
+
+        if vec3::dot(&normal, &ray.get_direction()) > 0. {
+            normal = [-normal[0], -normal[1], -normal[2]];
+        }
+
+        hit.normal = normal;
+
+        Some(hit)
+    }
+
     fn init(&mut self, gl: &web_sys::WebGl2RenderingContext) {
         self.init_renderer(gl);
+
+        let material: &Material = self.get_component().unwrap();
+        let program = self.get_program();
+
+        for warning in material.validate(&program) {
+            program.warn(&warning);
+        }
     }
 
     fn render(&mut self, gl: &web_sys::WebGl2RenderingContext, camera: &mut CameraRenderer) {
-        let projection_matrix = camera.get_projection_matrix();
         let transform: &Transform = self.get_component().unwrap();
         let world_matrix = transform.get_matrix();
-        let view_matrix = camera.get_camera_matrix();
         let mut normal_matrix = mat4::new_identity::<f32>();
         mat4::inv(&mut normal_matrix, &world_matrix);
         let matrix_clone = normal_matrix.clone();
@@ -24,20 +103,25 @@ pub trait ObjectModel: HasComponents + HasControllers + HasComponent<Transform>
 
         let program = self.get_program();
 
-        gl.use_program(Some(&program));
-        
-        let projection = gl.get_uniform_location(program, "u_projection");
-        gl.uniform_matrix4fv_with_f32_array(projection.as_ref(), false, &projection_matrix);
+        gl.use_program(Some(program.get_program()));
 
-        let view = gl.get_uniform_location(program, "u_view");
-        gl.uniform_matrix4fv_with_f32_array(view.as_ref(), false, &view_matrix);
+        // Projection, view and lights come from the `Camera` and `Frame` blocks bound by the scene.
+        program.set_uniform(gl, "u_world", &world_matrix);
+        program.set_uniform(gl, "u_normal", &normal_matrix);
 
-        let world = gl.get_uniform_location(program, "u_world");
-        gl.uniform_matrix4fv_with_f32_array(world.as_ref(), false, &world_matrix);
-
-        let normal = gl.get_uniform_location(program, "u_normal");
-        gl.uniform_matrix4fv_with_f32_array(normal.as_ref(), false, &normal_matrix);
+        let material: &Material = self.get_component().unwrap();
+        material.apply(gl, &program);
 
         self.render_renderer(gl, camera);
     }
-}
\ No newline at end of file
+
+    fn render_with_shader(&mut self, gl: &web_sys::WebGl2RenderingContext, camera: &mut CameraRenderer, shader: &Shader) {
+        let original: Shader = HasComponent::<Shader>::get_component(self).unwrap().clone();
+
+        self.attach_component(shader.clone());
+
+        self.render(gl, camera);
+
+        self.attach_component(original);
+    }
+}
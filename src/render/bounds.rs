@@ -0,0 +1,237 @@
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingBox {
+    min: [f32; 3],
+    max: [f32; 3],
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        BoundingBox {
+            min: [0f32; 3],
+            max: [0f32; 3],
+        }
+    }
+}
+
+impl BoundingBox {
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        BoundingBox { min, max }
+    }
+
+    pub fn from_vertices(vertices: &[f32]) -> Self {
+        if vertices.len() < 3 {
+            return BoundingBox::default();
+        }
+
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+
+        for vertex in vertices.chunks_exact(3) {
+            for i in 0..3 {
+                min[i] = min[i].min(vertex[i]);
+                max[i] = max[i].max(vertex[i]);
+            }
+        }
+
+        BoundingBox { min, max }
+    }
+
+    pub fn get_min(&self) -> [f32; 3] {
+        self.min
+    }
+
+    pub fn get_max(&self) -> [f32; 3] {
+        self.max
+    }
+
+    pub fn get_center(&self) -> [f32; 3] {
+        [
+            (self.min[0] + self.max[0]) / 2.,
+            (self.min[1] + self.max[1]) / 2.,
+            (self.min[2] + self.max[2]) / 2.,
+        ]
+    }
+
+    pub fn get_extents(&self) -> [f32; 3] {
+        [
+            (self.max[0] - self.min[0]) / 2.,
+            (self.max[1] - self.min[1]) / 2.,
+            (self.max[2] - self.min[2]) / 2.,
+        ]
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && self.max[i] >= other.min[i])
+    }
+
+    pub fn transform(&self, matrix: &[f32; 16]) -> BoundingBox {
+        let center = self.get_center();
+        let extents = self.get_extents();
+
+        let mut min = [0f32; 3];
+        let mut max = [0f32; 3];
+
+        // Arvo's method: project the extents on every axis of the new space.
+        for i in 0..3 {
+            let mut c = matrix[12 + i];
+            let mut e = 0.;
+
+            for j in 0..3 {
+                c += matrix[j * 4 + i] * center[j];
+                e += matrix[j * 4 + i].abs() * extents[j];
+            }
+
+            min[i] = c - e;
+            max[i] = c + e;
+        }
+
+        BoundingBox { min, max }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BoundingSphere {
+    center: [f32; 3],
+    radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: [f32; 3], radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    pub fn from_vertices(vertices: &[f32], center: [f32; 3]) -> Self {
+        let radius = vertices
+            .chunks_exact(3)
+            .map(|v| {
+                let d = [v[0] - center[0], v[1] - center[1], v[2] - center[2]];
+
+                (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+            })
+            .fold(0f32, f32::max);
+
+        BoundingSphere { center, radius }
+    }
+
+    pub fn get_center(&self) -> [f32; 3] {
+        self.center
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn transform(&self, matrix: &[f32; 16]) -> BoundingSphere {
+        let c = self.center;
+
+        let center = [
+            matrix[0] * c[0] + matrix[4] * c[1] + matrix[8] * c[2] + matrix[12],
+            matrix[1] * c[0] + matrix[5] * c[1] + matrix[9] * c[2] + matrix[13],
+            matrix[2] * c[0] + matrix[6] * c[1] + matrix[10] * c[2] + matrix[14],
+        ];
+
+        let scale = (0..3)
+            .map(|j| {
+                let axis = [matrix[j * 4], matrix[j * 4 + 1], matrix[j * 4 + 2]];
+
+                (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt()
+            })
+            .fold(0f32, f32::max);
+
+        BoundingSphere {
+            center,
+            radius: self.radius * scale,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bounds {
+    pub aabb: BoundingBox,
+    pub sphere: BoundingSphere,
+}
+
+impl Bounds {
+    pub fn from_vertices(vertices: &[f32]) -> Self {
+        let aabb = BoundingBox::from_vertices(vertices);
+
+        let sphere = BoundingSphere::from_vertices(vertices, aabb.get_center());
+
+        Bounds { aabb, sphere }
+    }
+
+    pub fn transform(&self, matrix: &[f32; 16]) -> Bounds {
+        Bounds {
+            aabb: self.aabb.transform(matrix),
+            sphere: self.sphere.transform(matrix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(value: [f32; 3], expected: [f32; 3]) {
+        for i in 0..3 {
+            assert!((value[i] - expected[i]).abs() < 1e-5, "{:?} != {:?}", value, expected);
+        }
+    }
+
+    /// Rotation of `angle` around z followed by a translation, column-major.
+    fn rotate_z(angle: f32, translation: [f32; 3]) -> [f32; 16] {
+        let (sin, cos) = angle.sin_cos();
+
+        [
+            cos, sin, 0., 0., //
+            -sin, cos, 0., 0., //
+            0., 0., 1., 0., //
+            translation[0], translation[1], translation[2], 1.,
+        ]
+    }
+
+    #[test]
+    fn boxes_follow_rotations_and_translations() {
+        let aabb = BoundingBox::new([0., 0., 0.], [2., 1., 1.]);
+
+        // x goes to y and y to -x.
+        let moved = aabb.transform(&rotate_z(std::f32::consts::FRAC_PI_2, [1., 2., 3.]));
+
+        assert_near(moved.get_min(), [0., 2., 3.]);
+        assert_near(moved.get_max(), [1., 4., 4.]);
+    }
+
+    #[test]
+    fn boxes_grow_to_enclose_rotated_corners() {
+        let aabb = BoundingBox::new([-1., -1., -1.], [1., 1., 1.]);
+        let moved = aabb.transform(&rotate_z(std::f32::consts::FRAC_PI_4, [0., 0., 0.]));
+        let diagonal = std::f32::consts::SQRT_2;
+
+        assert_near(moved.get_min(), [-diagonal, -diagonal, -1.]);
+        assert_near(moved.get_max(), [diagonal, diagonal, 1.]);
+    }
+
+    #[test]
+    fn boxes_follow_negative_scales() {
+        let mut matrix = rotate_z(0., [0., 0., 0.]);
+
+        matrix[0] = -2.;
+
+        let moved = BoundingBox::new([1., 0., 0.], [3., 1., 1.]).transform(&matrix);
+
+        assert_near(moved.get_min(), [-6., 0., 0.]);
+        assert_near(moved.get_max(), [-2., 1., 1.]);
+    }
+
+    #[test]
+    fn spheres_scale_by_the_largest_axis() {
+        let mut matrix = rotate_z(std::f32::consts::FRAC_PI_2, [1., 0., 0.]);
+
+        matrix[10] = 3.;
+
+        let moved = BoundingSphere::new([1., 0., 0.], 2.).transform(&matrix);
+
+        assert_near(moved.get_center(), [1., 1., 0.]);
+        assert!((moved.get_radius() - 6.).abs() < 1e-5);
+    }
+}
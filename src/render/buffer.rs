@@ -1,72 +1,438 @@
-use web_sys::{WebGlBuffer, WebGl2RenderingContext};
+use crate::render::Bounds;
+use web_sys::{WebGlBuffer, WebGl2RenderingContext, WebGlVertexArrayObject};
+
+/// Attribute locations every shader declares with `layout(location = ...)`, so one vertex
+/// array object works with any program.
+pub const POSITION_LOCATION: u32 = 0;
+pub const NORMAL_LOCATION: u32 = 1;
+
+/// Vertices a mesh can have to be drawn with u16 indices. WebGL2 always restarts primitives
+/// at the largest index of the type, so 65535 is not a usable u16 index.
+pub const MAX_U16_VERTICES: usize = u16::MAX as usize;
+
+/// Borrowed indices of a mesh, in the type they are stored with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indices<'a> {
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+}
+
+impl<'a> Indices<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> u32 {
+        match self {
+            Indices::U16(indices) => indices[index] as u32,
+            Indices::U32(indices) => indices[index],
+        }
+    }
+
+    /// Vertex indices of the triangle at `triangle`, for meshes drawn as `TRIANGLES`.
+    pub fn get_triangle(&self, triangle: usize) -> [u32; 3] {
+        [self.get(triangle * 3), self.get(triangle * 3 + 1), self.get(triangle * 3 + 2)]
+    }
+
+    pub fn get_triangle_count(&self) -> usize {
+        self.len() / 3
+    }
+}
+
+impl<'a> From<&'a [u16]> for Indices<'a> {
+    fn from(indices: &'a [u16]) -> Self {
+        Indices::U16(indices)
+    }
+}
+
+impl<'a> From<&'a [u32]> for Indices<'a> {
+    fn from(indices: &'a [u32]) -> Self {
+        Indices::U32(indices)
+    }
+}
+
+/// `indices` as u16 when every vertex is below `MAX_U16_VERTICES`, so none is the restart index.
+fn narrow_indices(indices: &[u32]) -> Option<Vec<u16>> {
+    if indices.iter().all(|&index| (index as usize) < MAX_U16_VERTICES) {
+        Some(indices.iter().map(|&index| index as u16).collect())
+    } else {
+        None
+    }
+}
+
+/// Whether `count` floats written at the float `offset` fit in `size` bytes.
+fn check_update(size: usize, offset: usize, count: usize) -> Result<(), &'static str> {
+    if (offset + count) * 4 > size {
+        return Err("update is out of the bounds of the buffer");
+    }
+
+    Ok(())
+}
+
+/// Storage only grows, so a mesh alternating between sizes is not reallocated each time.
+fn get_orphan_size(size: usize, bytes: usize) -> usize {
+    size.max(bytes)
+}
+
+/// How often the content of a buffer changes, a hint for where the driver stores it.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum BufferUsage {
+    /// Uploaded once and drawn many times.
+    #[default]
+    STATIC,
+    /// Modified repeatedly and drawn many times, such as cloth or water deformed each frame.
+    DYNAMIC,
+    /// Modified before nearly every draw, such as line trails rebuilt each frame.
+    STREAM,
+}
+
+impl BufferUsage {
+    pub fn to_gl(self) -> u32 {
+        match self {
+            BufferUsage::STATIC => WebGl2RenderingContext::STATIC_DRAW,
+            BufferUsage::DYNAMIC => WebGl2RenderingContext::DYNAMIC_DRAW,
+            BufferUsage::STREAM => WebGl2RenderingContext::STREAM_DRAW,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Buffers {
     pub vertex: Option<WebGlBuffer>,
     pub index: Option<WebGlBuffer>,
-    pub normal: Option<WebGlBuffer>
+    pub normal: Option<WebGlBuffer>,
+    /// `UNSIGNED_SHORT` or `UNSIGNED_INT`, the type `draw_elements` reads the index buffer as.
+    pub index_type: u32,
+    /// Attribute layout and index buffer of the mesh, bound to draw it.
+    pub vao: Option<WebGlVertexArrayObject>,
+    usage: BufferUsage,
+    /// Bytes allocated for every buffer, which can be more than the data last written.
+    vertex_size: usize,
+    normal_size: usize,
+    index_size: usize,
+    index_count: usize,
+    /// Copy of the vertices last written, for raycasts and bounds to follow the drawn shape.
+    vertices: Vec<f32>,
+    bounds: Bounds,
 }
 
 impl Buffers {
-    pub fn new(gl: &WebGl2RenderingContext, verticies: &[f32], indicies: &[u16], normals: &[f32]) -> Result<Self, &'static str> {
-        Ok(
-            Buffers {
-                vertex: Some(Buffers::init_vertex_buffer(&gl, verticies)?),
-                index: Some(Buffers::init_index_buffer(&gl, indicies)?),
-                normal: Some(Buffers::init_normal_buffer(&gl, normals)?)
-            }
-        )
+    /// Indices are stored as u16 whenever every vertex fits, u32 indices of small meshes included.
+    pub fn new<'a>(gl: &WebGl2RenderingContext, verticies: &[f32], indicies: impl Into<Indices<'a>>, normals: &[f32]) -> Result<Self, &'static str> {
+        Buffers::new_with_usage(gl, verticies, indicies, normals, BufferUsage::STATIC)
     }
 
-    fn init_buffer_f32(gl: &WebGl2RenderingContext, target: u32, vector: &[f32]) -> Result<WebGlBuffer, &'static str> {
-        let buffer = gl.create_buffer().ok_or("failed to creat buffer")?;
-    
-        gl.bind_buffer(target, Some(&buffer));
-    
+    /// Buffers meant to be modified after creation with `update_*` and `set_*`.
+    pub fn new_with_usage<'a>(
+        gl: &WebGl2RenderingContext,
+        verticies: &[f32],
+        indicies: impl Into<Indices<'a>>,
+        normals: &[f32],
+        usage: BufferUsage,
+    ) -> Result<Self, &'static str> {
+        let vao = gl.create_vertex_array().ok_or("failed to create vertex array")?;
+
+        gl.bind_vertex_array(Some(&vao));
+
+        let indicies = indicies.into();
+
+        let (index, index_type, index_size) = Buffers::init_index_buffer(gl, indicies, usage)?;
+
+        let buffers = Buffers {
+            vertex: Some(Buffers::init_vertex_buffer(gl, verticies, usage)?),
+            index: Some(index),
+            normal: Some(Buffers::init_normal_buffer(gl, normals, usage)?),
+            index_type,
+            vao: Some(vao),
+            usage,
+            vertex_size: verticies.len() * 4,
+            normal_size: normals.len() * 4,
+            index_size,
+            index_count: indicies.len(),
+            vertices: verticies.to_vec(),
+            bounds: Bounds::from_vertices(verticies),
+        };
+
+        Buffers::init_attribute(gl, buffers.vertex.as_ref(), POSITION_LOCATION);
+        Buffers::init_attribute(gl, buffers.normal.as_ref(), NORMAL_LOCATION);
+
+        // The element array binding is part of the vertex array, it must stay bound until here.
+        gl.bind_vertex_array(None);
+
+        Ok(buffers)
+    }
+
+    /// Makes the vertex array of the mesh current, `draw_elements` then needs no other binding.
+    pub fn bind(&self, gl: &WebGl2RenderingContext) {
+        gl.bind_vertex_array(self.vao.as_ref());
+    }
+
+    pub fn get_usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    /// Indices written by the last `set_indices`, or at creation.
+    pub fn get_index_count(&self) -> usize {
+        self.index_count
+    }
+
+    /// Vertices written at creation or by the last `set_vertices` and `update_vertices`.
+    pub fn get_vertices(&self) -> &[f32] {
+        &self.vertices
+    }
+
+    /// Local bounds of `get_vertices`, kept up to date by every write.
+    pub fn get_bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Overwrites vertices from the float at `offset`, keeping the rest of the buffer.
+    /// Only the vertices last written can be overwritten, not the spare storage after them.
+    pub fn update_vertices(&mut self, gl: &WebGl2RenderingContext, offset: usize, verticies: &[f32]) -> Result<(), &'static str> {
+        self.write_vertices(offset, verticies)?;
+
+        Buffers::update_buffer_f32(gl, self.vertex.as_ref(), self.vertex_size, offset, verticies)
+    }
+
+    /// Overwrites normals from the float at `offset`, keeping the rest of the buffer.
+    pub fn update_normals(&self, gl: &WebGl2RenderingContext, offset: usize, normals: &[f32]) -> Result<(), &'static str> {
+        Buffers::update_buffer_f32(gl, self.normal.as_ref(), self.normal_size, offset, normals)
+    }
+
+    /// Replaces every vertex. The previous storage is orphaned rather than overwritten, so
+    /// writing does not wait for draws still reading it. It only grows, a smaller mesh reuses it.
+    pub fn set_vertices(&mut self, gl: &WebGl2RenderingContext, verticies: &[f32]) {
+        self.vertices = verticies.to_vec();
+        self.bounds = Bounds::from_vertices(verticies);
+
+        let target = WebGl2RenderingContext::ARRAY_BUFFER;
+
+        gl.bind_buffer(target, self.vertex.as_ref());
+
         unsafe {
-            let array = js_sys::Float32Array::view(&vector);
-    
-            gl.buffer_data_with_array_buffer_view(
-                target,
-                &array,
-                WebGl2RenderingContext::STATIC_DRAW
-            );
+            let array = js_sys::Float32Array::view(verticies);
+
+            self.vertex_size = Buffers::orphan(gl, target, self.vertex_size, verticies.len() * 4, self.usage, &array);
         }
 
-        gl.bind_buffer(target, Some(&buffer));
-    
-        Ok(buffer)
+        gl.bind_buffer(target, None);
     }
-    
-    fn init_buffer_u16(gl: &WebGl2RenderingContext, target: u32, vector: &[u16]) -> Result<WebGlBuffer, &'static str> {
-        let buffer = gl.create_buffer().ok_or("failed to creat buffer")?;
-    
-        gl.bind_buffer(target, Some(&buffer));
-    
+
+    /// Replaces every normal, orphaning the previous storage like `set_vertices`.
+    pub fn set_normals(&mut self, gl: &WebGl2RenderingContext, normals: &[f32]) {
+        let target = WebGl2RenderingContext::ARRAY_BUFFER;
+
+        gl.bind_buffer(target, self.normal.as_ref());
+
+        unsafe {
+            let array = js_sys::Float32Array::view(normals);
+
+            self.normal_size = Buffers::orphan(gl, target, self.normal_size, normals.len() * 4, self.usage, &array);
+        }
+
+        gl.bind_buffer(target, None);
+    }
+
+    /// Replaces every index, orphaning the previous storage like `set_vertices`.
+    ///
+    /// The index type is chosen again, so a mesh can grow past `MAX_U16_VERTICES` vertices.
+    pub fn set_indices<'a>(&mut self, gl: &WebGl2RenderingContext, indicies: impl Into<Indices<'a>>) {
+        let indicies = indicies.into();
+
+        // The element array binding belongs to the vertex array, binding it alone would change another mesh.
+        self.bind(gl);
+
+        gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, self.index.as_ref());
+
+        let (size, usage) = (self.index_size, self.usage);
+
+        let (index_type, index_size) = Buffers::with_index_view(indicies, |array, index_type, bytes| {
+            (index_type, Buffers::orphan(gl, WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, size, bytes, usage, array))
+        });
+
+        gl.bind_vertex_array(None);
+
+        self.index_type = index_type;
+        self.index_size = index_size;
+        self.index_count = indicies.len();
+    }
+
+    /// CPU side of `update_vertices`.
+    fn write_vertices(&mut self, offset: usize, verticies: &[f32]) -> Result<(), &'static str> {
+        check_update(self.vertices.len() * 4, offset, verticies.len())?;
+
+        self.vertices[offset..offset + verticies.len()].copy_from_slice(verticies);
+        self.bounds = Bounds::from_vertices(&self.vertices);
+
+        Ok(())
+    }
+
+    fn init_attribute(gl: &WebGl2RenderingContext, buffer: Option<&WebGlBuffer>, location: u32) {
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, buffer);
+
+        gl.vertex_attrib_pointer_with_i32(location, 3, WebGl2RenderingContext::FLOAT, false, 0, 0);
+
+        gl.enable_vertex_attrib_array(location);
+    }
+
+    /// Reallocates the bound buffer with `usage` and writes `array` at its start, returning the new size.
+    fn orphan(gl: &WebGl2RenderingContext, target: u32, size: usize, bytes: usize, usage: BufferUsage, array: &js_sys::Object) -> usize {
+        let size = get_orphan_size(size, bytes);
+
+        gl.buffer_data_with_i32(target, size as i32, usage.to_gl());
+
+        gl.buffer_sub_data_with_i32_and_array_buffer_view(target, 0, array);
+
+        size
+    }
+
+    fn update_buffer_f32(gl: &WebGl2RenderingContext, buffer: Option<&WebGlBuffer>, size: usize, offset: usize, vector: &[f32]) -> Result<(), &'static str> {
+        check_update(size, offset, vector.len())?;
+
+        let target = WebGl2RenderingContext::ARRAY_BUFFER;
+
+        gl.bind_buffer(target, buffer);
+
         unsafe {
-            let array = js_sys::Uint16Array::view(&vector);
-    
-            gl.buffer_data_with_array_buffer_view(
-                target,
-                &array,
-                WebGl2RenderingContext::STATIC_DRAW
-            );
+            let array = js_sys::Float32Array::view(vector);
+
+            gl.buffer_sub_data_with_i32_and_array_buffer_view(target, (offset * 4) as i32, &array);
         }
 
+        gl.bind_buffer(target, None);
+
+        Ok(())
+    }
+
+    fn init_buffer(gl: &WebGl2RenderingContext, target: u32, array: &js_sys::Object, usage: BufferUsage) -> Result<WebGlBuffer, &'static str> {
+        let buffer = gl.create_buffer().ok_or("failed to creat buffer")?;
+
         gl.bind_buffer(target, Some(&buffer));
-    
+
+        gl.buffer_data_with_array_buffer_view(target, array, usage.to_gl());
+
         Ok(buffer)
     }
-    
-    fn init_vertex_buffer(gl: &WebGl2RenderingContext, verticies: &[f32]) -> Result<WebGlBuffer, &'static str> {
-        Buffers::init_buffer_f32(gl, WebGl2RenderingContext::ARRAY_BUFFER, verticies)
+
+    fn init_buffer_f32(gl: &WebGl2RenderingContext, target: u32, vector: &[f32], usage: BufferUsage) -> Result<WebGlBuffer, &'static str> {
+        unsafe {
+            let array = js_sys::Float32Array::view(vector);
+
+            Buffers::init_buffer(gl, target, &array, usage)
+        }
+    }
+
+    /// Calls `upload` with a view of `indicies` in the type they are stored with, narrowed to u16
+    /// when every vertex fits, along with that type and the size of the view in bytes.
+    fn with_index_view<T>(indicies: Indices, upload: impl FnOnce(&js_sys::Object, u32, usize) -> T) -> T {
+        // Views alias wasm memory, nothing may allocate until `upload` returns.
+        match indicies {
+            Indices::U16(indicies) => unsafe {
+                upload(&js_sys::Uint16Array::view(indicies), WebGl2RenderingContext::UNSIGNED_SHORT, indicies.len() * 2)
+            },
+            Indices::U32(indicies) => match narrow_indices(indicies) {
+                Some(indicies) => unsafe {
+                    upload(&js_sys::Uint16Array::view(&indicies), WebGl2RenderingContext::UNSIGNED_SHORT, indicies.len() * 2)
+                },
+                None => unsafe {
+                    upload(&js_sys::Uint32Array::view(indicies), WebGl2RenderingContext::UNSIGNED_INT, indicies.len() * 4)
+                },
+            },
+        }
+    }
+
+    fn init_vertex_buffer(gl: &WebGl2RenderingContext, verticies: &[f32], usage: BufferUsage) -> Result<WebGlBuffer, &'static str> {
+        Buffers::init_buffer_f32(gl, WebGl2RenderingContext::ARRAY_BUFFER, verticies, usage)
+    }
+
+    /// Returns the buffer with its index type and size in bytes.
+    fn init_index_buffer(gl: &WebGl2RenderingContext, indicies: Indices, usage: BufferUsage) -> Result<(WebGlBuffer, u32, usize), &'static str> {
+        Buffers::with_index_view(indicies, |array, index_type, bytes| {
+            Ok((Buffers::init_buffer(gl, WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, array, usage)?, index_type, bytes))
+        })
+    }
+
+    fn init_normal_buffer(gl: &WebGl2RenderingContext, normals: &[f32], usage: BufferUsage) -> Result<WebGlBuffer, &'static str> {
+        Buffers::init_buffer_f32(gl, WebGl2RenderingContext::ARRAY_BUFFER, normals, usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffers(vertices: &[f32]) -> Buffers {
+        Buffers {
+            vertex: None,
+            index: None,
+            normal: None,
+            index_type: WebGl2RenderingContext::UNSIGNED_SHORT,
+            vao: None,
+            usage: BufferUsage::DYNAMIC,
+            vertex_size: vertices.len() * 4,
+            normal_size: vertices.len() * 4,
+            index_size: 0,
+            index_count: 0,
+            vertices: vertices.to_vec(),
+            bounds: Bounds::from_vertices(vertices),
+        }
+    }
+
+    #[test]
+    fn updates_stay_in_the_buffer() {
+        assert!(check_update(24, 0, 6).is_ok());
+        assert!(check_update(24, 3, 3).is_ok());
+        assert!(check_update(24, 4, 3).is_err());
+        assert!(check_update(24, 7, 0).is_err());
+        assert!(check_update(0, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn orphaned_storage_only_grows() {
+        assert_eq!(get_orphan_size(64, 32), 64);
+        assert_eq!(get_orphan_size(64, 128), 128);
+        assert_eq!(get_orphan_size(0, 0), 0);
+    }
+
+    #[test]
+    fn partial_updates_move_the_bounds() {
+        let mut buffers = buffers(&[0., 0., 0., 1., 1., 1.]);
+
+        buffers.write_vertices(3, &[4., 2., -1.]).unwrap();
+
+        assert_eq!(buffers.get_vertices(), &[0., 0., 0., 4., 2., -1.]);
+        assert_eq!(buffers.get_bounds().aabb.get_min(), [0., 0., -1.]);
+        assert_eq!(buffers.get_bounds().aabb.get_max(), [4., 2., 0.]);
+
+        assert!(buffers.write_vertices(4, &[0., 0., 0.]).is_err());
+        assert_eq!(buffers.get_vertices(), &[0., 0., 0., 4., 2., -1.]);
     }
-    
-    fn init_index_buffer(gl: &WebGl2RenderingContext, indicies: &[u16]) -> Result<WebGlBuffer, &'static str> {
-        Buffers::init_buffer_u16(gl, WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, indicies)
+
+    #[test]
+    fn indices_read_in_either_type() {
+        let wide = [0u32, 1, 2, 2, 1, 70000];
+        let narrow = [0u16, 1, 2, 2, 1, 3];
+
+        assert_eq!(Indices::from(&wide[..]).get_triangle(1), [2, 1, 70000]);
+        assert_eq!(Indices::from(&narrow[..]).get_triangle(1), [2, 1, 3]);
+        assert_eq!(Indices::from(&narrow[..]).get_triangle_count(), 2);
+        assert!(Indices::U16(&[]).is_empty());
     }
 
-    fn init_normal_buffer(gl: &WebGl2RenderingContext, normals: &[f32]) -> Result<WebGlBuffer, &'static str> {
-        Buffers::init_buffer_f32(gl, WebGl2RenderingContext::ARRAY_BUFFER, normals)
+    #[test]
+    fn indices_narrow_below_the_restart_index() {
+        let last = (MAX_U16_VERTICES - 1) as u32;
+
+        assert_eq!(narrow_indices(&[0, 1, last]), Some(vec![0, 1, 65534]));
+        assert_eq!(narrow_indices(&[0, 1, last + 1]), None);
+        assert_eq!(narrow_indices(&[0, 1, 100000]), None);
+        assert_eq!(narrow_indices(&[]), Some(vec![]));
     }
-}
\ No newline at end of file
+}
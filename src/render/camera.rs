@@ -1,13 +1,15 @@
-use crate::render::Renderable;
+use crate::render::{Frustum, Projection, Ray, Renderable, Viewport};
 use web_sys::WebGl2RenderingContext;
 
 #[derive (Clone, Debug)]
 pub struct CameraRenderer {
-    fov: f32,
+    projection: Projection,
     aspect: f32,
-    near: f32,
-    far: f32,
-    viewport: [f32; 4],
+    viewport: Viewport,
+    clear_color: Option<[f32; 4]>,
+    clear_depth: bool,
+    priority: i32,
+    layer_mask: u32,
     camera_matrix: [f32; 16],
     projection_matrix: [f32; 16],
     max_width: u32,
@@ -18,11 +20,13 @@ pub struct CameraRenderer {
 impl Default for CameraRenderer {
     fn default() -> CameraRenderer {
         CameraRenderer {
-            fov: 30.,
+            projection: Projection::default(),
             aspect: 0.,
-            near: 0.01,
-            far: 1000.,
-            viewport: [0., 0., 1., 1.],
+            viewport: Viewport::default(),
+            clear_color: Some([0., 0., 0., 1.]),
+            clear_depth: true,
+            priority: 0,
+            layer_mask: u32::MAX,
             camera_matrix: [0f32; 16],
             projection_matrix: [0f32; 16],
             max_width: 1920u32,
@@ -33,16 +37,26 @@ impl Default for CameraRenderer {
 }
 
 impl CameraRenderer {
+    pub fn set_projection(&mut self, projection: Projection) {
+        if self.projection != projection {
+            self.projection_update = true;
+        }
+
+        self.projection = projection;
+    }
+
+    pub fn get_projection(&self) -> &Projection {
+        &self.projection
+    }
+
     pub fn set_fov(&mut self, fov: f32) {
-        if self.fov != fov {
+        if self.projection.set_fov(fov) {
             self.projection_update = true;
         }
-        
-        self.fov = fov;
     }
 
-    pub fn get_fov(&self) -> f32 {
-        self.fov
+    pub fn get_fov(&self) -> Option<f32> {
+        self.projection.get_fov()
     }
 
     pub fn set_aspect(&mut self, aspect: f32) {
@@ -58,31 +72,37 @@ impl CameraRenderer {
     }
 
     pub fn set_near(&mut self, near: f32) {
-        if self.near != near {
+        if self.projection.set_near(near) {
             self.projection_update = true;
         }
-        
-        self.near = near;
     }
 
-    pub fn get_near(&self) -> f32 {
-        self.near
+    pub fn get_near(&self) -> Option<f32> {
+        self.projection.get_near()
     }
 
     pub fn set_far(&mut self, far: f32) {
-        if self.far != far {
+        if self.projection.set_far(far) {
             self.projection_update = true;
         }
-        
-        self.far = far
     }
 
-    pub fn get_far(&self) -> f32 {
-        self.far
+    pub fn get_far(&self) -> Option<f32> {
+        self.projection.get_far()
     }
 
-    pub fn set_viewport(&mut self, viewport: [f32; 4]) {
+    pub fn set_viewport(&mut self, viewport: Viewport) {
         self.viewport = viewport;
+
+        self.update_aspect();
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn get_viewport_pixels(&self) -> [i32; 4] {
+        self.viewport.to_pixels(self.max_width, self.max_height)
     }
 
     pub fn set_canvas_max(&mut self, width: u32, height: u32) {
@@ -92,10 +112,44 @@ impl CameraRenderer {
 
         self.max_width = width;
         self.max_height = height;
+
+        self.update_aspect();
     }
 
-    pub fn get_viewport(&self) -> [f32; 4] {
-        self.viewport
+    pub fn set_clear_color(&mut self, clear_color: Option<[f32; 4]>) {
+        self.clear_color = clear_color;
+    }
+
+    pub fn get_clear_color(&self) -> Option<[f32; 4]> {
+        self.clear_color
+    }
+
+    pub fn set_clear_depth(&mut self, clear_depth: bool) {
+        self.clear_depth = clear_depth;
+    }
+
+    pub fn get_clear_depth(&self) -> bool {
+        self.clear_depth
+    }
+
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    pub fn get_priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn set_layer_mask(&mut self, layer_mask: u32) {
+        self.layer_mask = layer_mask;
+    }
+
+    pub fn get_layer_mask(&self) -> u32 {
+        self.layer_mask
+    }
+
+    pub fn is_layer_visible(&self, layer_mask: u32) -> bool {
+        self.layer_mask & layer_mask != 0
     }
 
     pub fn set_camera_matrix(&mut self, camera_matrix: [f32; 16]) {
@@ -106,77 +160,188 @@ impl CameraRenderer {
         self.camera_matrix
     }
 
-    pub fn get_projection_matrix(&mut self) -> [f32; 16] {
-        if self.projection_update {
-            self.projection_update = false;
+    /// World position of the camera, the translation of the inverse view matrix.
+    pub fn get_position(&self) -> [f32; 3] {
+        let mut inverse_matrix = mat4::new_identity::<f32>();
+        mat4::inv(&mut inverse_matrix, &self.camera_matrix);
 
-            self.update_projection_matrix();
-        }
+        [inverse_matrix[12], inverse_matrix[13], inverse_matrix[14]]
+    }
+
+    pub fn get_projection_matrix(&mut self) -> [f32; 16] {
+        self.update_projection_matrix();
 
         self.projection_matrix
     }
 
-    pub fn update_projection_matrix(&mut self) {
-        self.projection_matrix = self.calculate_projection_matrix();
+    pub fn get_frustum(&mut self) -> Frustum {
+        let projection_matrix = self.get_projection_matrix();
+
+        let mut matrix = mat4::new_identity::<f32>();
+
+        mat4::mul(&mut matrix, &projection_matrix, &self.camera_matrix);
+
+        Frustum::from_matrix(&matrix)
     }
 
-    pub fn attach_viewport(&self, gl: &web_sys::WebGl2RenderingContext) {
-        let v = self.get_viewport();
+    pub fn contains_screen_point(&self, x: f32, y: f32) -> bool {
+        let [vx, vy, width, height] = self.get_viewport_pixels();
+
+        let y = self.max_height as f32 - y;
 
-        let max_width = self.max_width;
+        x >= vx as f32 && x <= (vx + width) as f32 && y >= vy as f32 && y <= (vy + height) as f32
+    }
 
-        let max_height = self.max_height;
+    /// Ray going through the canvas pixel `(x, y)`, with the origin at the top left
+    /// like mouse events. Returns `None` when the point is outside the viewport.
+    pub fn screen_point_to_ray(&mut self, x: f32, y: f32) -> Option<Ray> {
+        if !self.contains_screen_point(x, y) {
+            return None;
+        }
 
-        let x = if v[0] > 0. && v[0] <= 1. {
-            max_width as f32 * v[0]
-        } else {
-            v[0]
-        };
+        let [vx, vy, width, height] = self.get_viewport_pixels();
 
-        let y = if v[1] > 0. && v[1] <= 1. {
-            max_height as f32 * v[1]
-        } else {
-            v[1]
-        };
+        let ndc_x = 2. * (x - vx as f32) / width as f32 - 1.;
+        let ndc_y = 2. * (self.max_height as f32 - y - vy as f32) / height as f32 - 1.;
 
-        let width = if v[2] <= 1. {
-            max_width as f32 * v[2]
-        } else {
-            v[2]
-        };
+        let projection_matrix = self.get_projection_matrix();
 
-        let height = if v[3] <= 1. {
-            max_height as f32 * v[3]
-        } else {
-            v[3]
-        };
+        let mut matrix = mat4::new_identity::<f32>();
+        mat4::mul(&mut matrix, &projection_matrix, &self.camera_matrix);
 
-        gl.viewport(x as i32, y as i32, width as i32, height as i32);
+        let mut inverse_matrix = mat4::new_identity::<f32>();
+        mat4::inv(&mut inverse_matrix, &matrix);
+
+        let mut near = [0f32; 3];
+        let mut far = [0f32; 3];
+
+        vec3::transform_mat4_projection(&mut near, &[ndc_x, ndc_y, -1.], &inverse_matrix);
+        vec3::transform_mat4_projection(&mut far, &[ndc_x, ndc_y, 1.], &inverse_matrix);
+
+        let mut direction = [0f32; 3];
+        vec3::sub(&mut direction, &far, &near);
+
+        Some(Ray::new(near, direction))
     }
 
-    fn calculate_projection_matrix(&self) -> [f32; 16] {
-        let mut matrix = mat4::new_identity::<f32>();
+    pub fn update_projection_matrix(&mut self) {
+        if self.projection_update {
+            self.projection_update = false;
+
+            self.projection_matrix = self.calculate_projection_matrix();
+        }
+    }
+
+    pub fn attach_viewport(&self, gl: &web_sys::WebGl2RenderingContext) {
+        let [x, y, width, height] = self.get_viewport_pixels();
+
+        gl.viewport(x, y, width, height);
 
-        let fov = self.get_fov();
+        gl.scissor(x, y, width, height);
+    }
 
-        let aspect = self.get_aspect();
+    pub fn clear(&self, gl: &web_sys::WebGl2RenderingContext) {
+        let mut mask = 0;
 
-        let near = self.get_near();
+        if let Some([r, g, b, a]) = self.clear_color {
+            gl.clear_color(r, g, b, a);
 
-        let far = self.get_far();
+            mask |= WebGl2RenderingContext::COLOR_BUFFER_BIT;
+        }
 
-        mat4::perspective(&mut matrix, &fov, &aspect, &near, &far);
+        if self.clear_depth {
+            gl.clear_depth(1.);
 
-        matrix
+            mask |= WebGl2RenderingContext::DEPTH_BUFFER_BIT;
+        }
+
+        if mask != 0 {
+            gl.clear(mask);
+        }
+    }
+
+    fn update_aspect(&mut self) {
+        let [_, _, width, height] = self.get_viewport_pixels();
+
+        if width > 0 && height > 0 {
+            self.set_aspect(width as f32 / height as f32);
+        }
+    }
+
+    fn calculate_projection_matrix(&self) -> [f32; 16] {
+        self.projection.calculate_matrix(self.get_aspect())
     }
 }
 
 impl Renderable for CameraRenderer {
     fn init(&mut self, gl: &WebGl2RenderingContext) {
+        self.projection_update = false;
+
         self.projection_matrix = self.calculate_projection_matrix();
     }
 
     fn render(&mut self, gl: &WebGl2RenderingContext, camera: &mut CameraRenderer) {
         panic!("Should not render camera.");
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance_to_ray(ray: &Ray, point: [f32; 3]) -> f32 {
+        let origin = ray.get_origin();
+        let offset = [point[0] - origin[0], point[1] - origin[1], point[2] - origin[2]];
+
+        let along = vec3::dot(&offset, &ray.get_direction());
+        let closest = ray.get_point(along);
+
+        let d = [point[0] - closest[0], point[1] - closest[1], point[2] - closest[2]];
+
+        vec3::dot(&d, &d).sqrt()
+    }
+
+    /// Square viewport on the right half of a 800x400 canvas, looking down -z from (0, 0, 5).
+    fn camera() -> CameraRenderer {
+        let mut camera = CameraRenderer::default();
+
+        camera.set_canvas_max(800, 400);
+        camera.set_viewport(Viewport::PIXELS([400, 0, 400, 400]));
+        camera.set_projection(Projection::PERSPECTIVE {
+            fov: std::f32::consts::FRAC_PI_2,
+            near: 1.,
+            far: 100.,
+        });
+
+        let mut view = mat4::new_identity::<f32>();
+
+        view[14] = -5.;
+
+        camera.set_camera_matrix(view);
+
+        camera
+    }
+
+    #[test]
+    fn screen_points_cast_rays_through_what_they_show() {
+        let mut camera = camera();
+
+        // (1, 2, -3) is 8 units in front of the camera, at (0.125, 0.25) in device coordinates.
+        let ray = camera.screen_point_to_ray(625., 150.).unwrap();
+
+        assert!(distance_to_ray(&ray, [1., 2., -3.]) < 1e-4);
+        assert!((ray.get_origin()[2] - 4.).abs() < 1e-4);
+        assert!(ray.get_direction()[2] < 0.);
+
+        let center = camera.screen_point_to_ray(600., 200.).unwrap();
+
+        assert!(distance_to_ray(&center, [0., 0., -50.]) < 1e-3);
+    }
+
+    #[test]
+    fn screen_points_outside_the_viewport_cast_no_ray() {
+        let mut camera = camera();
+
+        assert!(camera.screen_point_to_ray(100., 200.).is_none());
+        assert!(camera.screen_point_to_ray(600., 450.).is_none());
+    }
+}
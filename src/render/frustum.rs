@@ -0,0 +1,160 @@
+use crate::render::{BoundingBox, BoundingSphere, Bounds};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Frustum {
+    planes: [[f32; 4]; 6],
+}
+
+impl Frustum {
+    /// Extracts the six clipping planes (left, right, bottom, top, near, far) of a
+    /// projection * view matrix. Plane normals point inside the frustum.
+    pub fn from_matrix(m: &[f32; 16]) -> Self {
+        let row = |i: usize| [m[i], m[4 + i], m[8 + i], m[12 + i]];
+
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+        let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+
+        let mut planes = [
+            add(r3, r0),
+            sub(r3, r0),
+            add(r3, r1),
+            sub(r3, r1),
+            add(r3, r2),
+            sub(r3, r2),
+        ];
+
+        for plane in planes.iter_mut() {
+            let length = (plane[0] * plane[0] + plane[1] * plane[1] + plane[2] * plane[2]).sqrt();
+
+            if length > 0. {
+                for v in plane.iter_mut() {
+                    *v /= length;
+                }
+            }
+        }
+
+        Frustum { planes }
+    }
+
+    pub fn get_planes(&self) -> &[[f32; 4]; 6] {
+        &self.planes
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let c = sphere.get_center();
+        let r = sphere.get_radius();
+
+        self.planes
+            .iter()
+            .all(|p| p[0] * c[0] + p[1] * c[1] + p[2] * c[2] + p[3] >= -r)
+    }
+
+    pub fn intersects_box(&self, aabb: &BoundingBox) -> bool {
+        let min = aabb.get_min();
+        let max = aabb.get_max();
+
+        self.planes.iter().all(|p| {
+            // Corner of the box furthest along the plane normal.
+            let x = if p[0] >= 0. { max[0] } else { min[0] };
+            let y = if p[1] >= 0. { max[1] } else { min[1] };
+            let z = if p[2] >= 0. { max[2] } else { min[2] };
+
+            p[0] * x + p[1] * y + p[2] * z + p[3] >= 0.
+        })
+    }
+
+    pub fn intersects(&self, bounds: &Bounds) -> bool {
+        self.intersects_sphere(&bounds.sphere) && self.intersects_box(&bounds.aabb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 90° vertical field of view, square aspect, near 1 and far 10, seen from (0, 0, 5) down -z.
+    fn view_projection() -> [f32; 16] {
+        let (depth, offset) = (-11. / 9., -20. / 9.);
+
+        // The view only translates by -5 along z, folded in the last column of the projection.
+        [
+            1., 0., 0., 0., //
+            0., 1., 0., 0., //
+            0., 0., depth, -1., //
+            0., 0., depth * -5. + offset, 5.,
+        ]
+    }
+
+    fn assert_plane(plane: [f32; 4], expected: [f32; 4]) {
+        for i in 0..4 {
+            assert!((plane[i] - expected[i]).abs() < 1e-5, "{:?} != {:?}", plane, expected);
+        }
+    }
+
+    #[test]
+    fn planes_point_inside_the_frustum() {
+        let frustum = Frustum::from_matrix(&view_projection());
+        let planes = frustum.get_planes();
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+
+        assert_plane(planes[0], [s, 0., -s, 5. * s]);
+        assert_plane(planes[1], [-s, 0., -s, 5. * s]);
+        assert_plane(planes[2], [0., s, -s, 5. * s]);
+        assert_plane(planes[3], [0., -s, -s, 5. * s]);
+        assert_plane(planes[4], [0., 0., -1., 4.]);
+        assert_plane(planes[5], [0., 0., 1., 5.]);
+    }
+
+    #[test]
+    fn spheres_are_culled_outside_the_frustum() {
+        let frustum = Frustum::from_matrix(&view_projection());
+        let sphere = |center, radius| frustum.intersects_sphere(&BoundingSphere::new(center, radius));
+
+        assert!(sphere([0., 0., 0.], 0.5));
+        assert!(sphere([0., 0., -4.], 0.5));
+
+        // Behind the camera, beyond the far plane and left of the frustum.
+        assert!(!sphere([0., 0., 10.], 1.));
+        assert!(!sphere([0., 0., -7.], 1.));
+        assert!(!sphere([-20., 0., 0.], 1.));
+
+        // Straddling the near, the far and the right plane.
+        assert!(sphere([0., 0., 4.5], 1.));
+        assert!(sphere([0., 0., -5.5], 1.));
+        assert!(sphere([5.5, 0., 0.], 1.));
+    }
+
+    #[test]
+    fn boxes_are_culled_outside_the_frustum() {
+        let frustum = Frustum::from_matrix(&view_projection());
+        let aabb = |min, max| frustum.intersects_box(&BoundingBox::new(min, max));
+
+        assert!(aabb([-1., -1., -1.], [1., 1., 1.]));
+
+        // Right of the frustum, above it and behind the camera.
+        assert!(!aabb([10., -1., -1.], [12., 1., 1.]));
+        assert!(!aabb([-1., 10., -1.], [1., 12., 1.]));
+        assert!(!aabb([-1., -1., 6.], [1., 1., 8.]));
+
+        // Straddling the right, the bottom and the near plane.
+        assert!(aabb([4., -1., -1.], [6., 1., 1.]));
+        assert!(aabb([-1., -6., -1.], [1., -4., 1.]));
+        assert!(aabb([-1., -1., 3.], [1., 1., 5.]));
+
+        // Covering the whole frustum.
+        assert!(aabb([-100., -100., -100.], [100., 100., 100.]));
+    }
+
+    #[test]
+    fn bounds_need_both_volumes_inside() {
+        let frustum = Frustum::from_matrix(&view_projection());
+
+        let inside = Bounds::from_vertices(&[-1., -1., -1., 1., 1., 1.]);
+        let outside = Bounds::from_vertices(&[10., -1., -1., 12., 1., 1.]);
+
+        assert!(frustum.intersects(&inside));
+        assert!(!frustum.intersects(&outside));
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// Directional lights uploaded to the `Frame` block, the others are ignored.
+pub const MAX_LIGHTS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Light {
+    /// Added to every surface, ambient lights are summed.
+    AMBIENT { color: [f32; 3] },
+    /// `direction` points from the surfaces towards the light.
+    DIRECTIONAL { direction: [f32; 3], color: [f32; 3] },
+}
+
+/// Lights of a scene that does not set any.
+pub fn get_default_lights() -> Vec<Light> {
+    vec![
+        Light::AMBIENT { color: [0.3, 0.3, 0.3] },
+        Light::DIRECTIONAL {
+            direction: [0.85, 0.8, 0.75],
+            color: [1., 0.75, 1.],
+        },
+    ]
+}
+
+/// Lights of a scene as the `Frame` uniform block stores them, ambient lights summed.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct LightData {
+    pub ambient: [f32; 3],
+    pub directions: [[f32; 3]; MAX_LIGHTS],
+    pub colors: [[f32; 3]; MAX_LIGHTS],
+    pub count: usize,
+}
+
+impl LightData {
+    pub fn new(lights: &[Light]) -> Self {
+        let mut data = LightData::default();
+
+        for light in lights {
+            match light {
+                Light::AMBIENT { color } => {
+                    for (value, channel) in data.ambient.iter_mut().zip(color) {
+                        *value += channel;
+                    }
+                }
+                Light::DIRECTIONAL { direction, color } => {
+                    if data.count == MAX_LIGHTS {
+                        continue;
+                    }
+
+                    data.directions[data.count] = *direction;
+                    data.colors[data.count] = *color;
+
+                    data.count += 1;
+                }
+            }
+        }
+
+        data
+    }
+}
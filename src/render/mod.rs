@@ -1,12 +1,32 @@
 pub mod shader;
 pub mod primitives;
+mod bounds;
 mod camera;
+mod frustum;
+mod light;
 mod object;
+mod picking;
+mod program;
+mod projection;
+mod ray;
+mod uniform_buffer;
+mod viewport;
+mod watcher;
 pub mod buffer;
 
+pub use bounds::*;
 pub use camera::*;
+pub use frustum::*;
+pub use light::*;
+pub use picking::*;
+pub use program::*;
+pub use projection::*;
+pub use ray::*;
+pub use uniform_buffer::*;
+pub use viewport::*;
+pub use watcher::*;
 pub use primitives::*;
-pub use buffer::Buffers;
+pub use buffer::{BufferUsage, Buffers, Indices, MAX_U16_VERTICES, NORMAL_LOCATION, POSITION_LOCATION};
 pub use object::ObjectRenderer;
 pub use shader::*;
 
@@ -0,0 +1,254 @@
+use crate::component::{Component, Shader};
+use crate::engine::Entity;
+use crate::io::{Mouse, MouseButton};
+use crate::model::ObjectModel;
+use crate::render::{link_program_str, CameraRenderer, Preprocessor, ShaderProgram};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::{WebGl2RenderingContext, WebGlFramebuffer, WebGlRenderbuffer};
+
+/// Shared result of the picking pass, can be cloned into controllers.
+#[derive(Default, Clone, Debug)]
+pub struct Selection {
+    hovered: Rc<RefCell<Option<Entity>>>,
+    selected: Rc<RefCell<Option<Entity>>>,
+    requested: Rc<RefCell<bool>>,
+}
+
+impl Selection {
+    pub fn get_hovered(&self) -> Option<Entity> {
+        *self.hovered.borrow()
+    }
+
+    pub fn get_selected(&self) -> Option<Entity> {
+        *self.selected.borrow()
+    }
+
+    pub fn set_selected(&self, entity: Option<Entity>) {
+        *self.selected.borrow_mut() = entity;
+    }
+
+    /// Picks again on the next frame even if the cursor did not move, for objects moving under it.
+    pub fn request_pick(&self) {
+        *self.requested.borrow_mut() = true;
+    }
+}
+
+/// Offscreen pass drawing every object in a unique color to find the entity under the cursor.
+#[derive(Default, Clone, Debug)]
+pub struct Picking {
+    mouse: Mouse,
+    selection: Selection,
+    shader: Shader,
+    framebuffer: Option<WebGlFramebuffer>,
+    color: Option<WebGlRenderbuffer>,
+    depth: Option<WebGlRenderbuffer>,
+    width: i32,
+    height: i32,
+    /// Entities drawn this pass, an entity is drawn with its position in the list as color.
+    drawn: Vec<Entity>,
+    /// Cursor position of the last pick, reading pixels back stalls the GPU so it is only done when it changes.
+    picked_at: Option<(f32, f32)>,
+}
+
+impl Picking {
+    pub fn new(mouse: &Mouse) -> Self {
+        Picking {
+            mouse: mouse.clone(),
+            ..Picking::default()
+        }
+    }
+
+    pub fn get_selection(&self) -> Selection {
+        self.selection.clone()
+    }
+
+    /// Cursor position in canvas pixels.
+    pub fn get_mouse_position(&self) -> (f32, f32) {
+        self.mouse.get_canvas_position()
+    }
+
+    /// Whether the cursor moved, the primary button was pressed or a pick was requested since the last pick.
+    pub fn is_pending(&self) -> bool {
+        *self.selection.requested.borrow()
+            || self.mouse.is_pressed(MouseButton::PRIMARY)
+            || self.picked_at != Some(self.get_mouse_position())
+    }
+
+    pub fn init(&mut self, gl: &WebGl2RenderingContext) -> Result<(), &'static str> {
+        let mut preprocessor = Preprocessor::default();
+
+        preprocessor.set_file("picking_vert.glsl", include_str!("../../resources/shaders/picking_vert.glsl"));
+        preprocessor.set_file("picking_frag.glsl", include_str!("../../resources/shaders/picking_frag.glsl"));
+        preprocessor.set_file("camera.glsl", include_str!("../../resources/shaders/camera.glsl"));
+
+        let vertex = preprocessor.process("picking_vert.glsl", &[]).map_err(|_| "failed to preprocess picking shader")?;
+        let fragment = preprocessor.process("picking_frag.glsl", &[]).map_err(|_| "failed to preprocess picking shader")?;
+
+        let program = link_program_str(gl, &vertex, &fragment);
+
+        self.shader = Shader::from(ShaderProgram::new(gl, program));
+
+        self.framebuffer = Some(gl.create_framebuffer().ok_or("failed to create framebuffer")?);
+        self.color = Some(gl.create_renderbuffer().ok_or("failed to create renderbuffer")?);
+        self.depth = Some(gl.create_renderbuffer().ok_or("failed to create renderbuffer")?);
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.framebuffer.as_ref());
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, self.color.as_ref());
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::COLOR_ATTACHMENT0,
+            WebGl2RenderingContext::RENDERBUFFER,
+            self.color.as_ref(),
+        );
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, self.depth.as_ref());
+        gl.framebuffer_renderbuffer(
+            WebGl2RenderingContext::FRAMEBUFFER,
+            WebGl2RenderingContext::DEPTH_ATTACHMENT,
+            WebGl2RenderingContext::RENDERBUFFER,
+            self.depth.as_ref(),
+        );
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, None);
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+
+        Ok(())
+    }
+
+    fn resize(&mut self, gl: &WebGl2RenderingContext, width: i32, height: i32) {
+        if self.width == width && self.height == height {
+            return;
+        }
+
+        self.width = width;
+        self.height = height;
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, self.color.as_ref());
+        gl.renderbuffer_storage(WebGl2RenderingContext::RENDERBUFFER, WebGl2RenderingContext::RGBA8, width, height);
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, self.depth.as_ref());
+        gl.renderbuffer_storage(
+            WebGl2RenderingContext::RENDERBUFFER,
+            WebGl2RenderingContext::DEPTH_COMPONENT16,
+            width,
+            height,
+        );
+
+        gl.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, None);
+    }
+
+    pub fn begin(&mut self, gl: &WebGl2RenderingContext, width: u32, height: u32) -> bool {
+        if self.framebuffer.is_none() || width == 0 || height == 0 {
+            return false;
+        }
+
+        // The same pixel of a resized canvas can show another object.
+        let resized = self.width != width as i32 || self.height != height as i32;
+
+        if !resized && !self.is_pending() {
+            return false;
+        }
+
+        self.resize(gl, width as i32, height as i32);
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, self.framebuffer.as_ref());
+
+        gl.viewport(0, 0, self.width, self.height);
+        gl.clear_color(0., 0., 0., 0.);
+        gl.clear_depth(1.);
+        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+
+        self.drawn.clear();
+
+        true
+    }
+
+    pub fn render_object(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        object: &mut dyn ObjectModel,
+        camera: &mut CameraRenderer,
+        entity: Entity,
+    ) {
+        let program = self.shader.get_program();
+
+        gl.use_program(Some(program.get_program()));
+
+        program.set_uniform(gl, "u_id", &encode_id(self.drawn.len()));
+
+        self.drawn.push(entity);
+
+        object.render_with_shader(gl, camera, &self.shader);
+    }
+
+    pub fn end(&mut self, gl: &WebGl2RenderingContext) {
+        let position = self.get_mouse_position();
+        let (x, y) = (position.0.floor() as i32, position.1.floor() as i32);
+
+        let mut pixel = [0u8; 4];
+
+        let read = gl.read_pixels_with_opt_u8_array(
+            x,
+            self.height - y - 1,
+            1,
+            1,
+            WebGl2RenderingContext::RGBA,
+            WebGl2RenderingContext::UNSIGNED_BYTE,
+            Some(&mut pixel),
+        );
+
+        gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+
+        let hovered = if read.is_ok() && x >= 0 && y >= 0 && x < self.width && y < self.height {
+            decode_id(pixel).and_then(|id| self.drawn.get(id).copied())
+        } else {
+            None
+        };
+
+        *self.selection.hovered.borrow_mut() = hovered;
+        *self.selection.requested.borrow_mut() = false;
+
+        self.picked_at = Some(position);
+
+        if self.mouse.is_pressed(MouseButton::PRIMARY) {
+            self.selection.set_selected(hovered);
+        }
+    }
+}
+
+impl Component for Picking {
+    fn update(&mut self, _dt: f32) {}
+
+    fn on_destroy(&mut self, gl: &WebGl2RenderingContext) {
+        gl.delete_framebuffer(self.framebuffer.take().as_ref());
+        gl.delete_renderbuffer(self.color.take().as_ref());
+        gl.delete_renderbuffer(self.depth.take().as_ref());
+
+        self.width = 0;
+        self.height = 0;
+    }
+}
+
+/// Id `n` is drawn as color `n + 1` so that the cleared black means no entity.
+pub fn encode_id(id: usize) -> [f32; 4] {
+    let id = id + 1;
+
+    [
+        (id & 0xff) as f32 / 255.,
+        ((id >> 8) & 0xff) as f32 / 255.,
+        ((id >> 16) & 0xff) as f32 / 255.,
+        1.,
+    ]
+}
+
+pub fn decode_id(pixel: [u8; 4]) -> Option<usize> {
+    let id = pixel[0] as usize | (pixel[1] as usize) << 8 | (pixel[2] as usize) << 16;
+
+    if id == 0 {
+        None
+    } else {
+        Some(id - 1)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::render::{Buffers, ObjectRenderer, Renderable, CameraRenderer};
+use crate::render::{Buffers, Indices, ObjectRenderer, Renderable, CameraRenderer};
 use crate::component::{HasComponent, Shader, HasComponents};
 
 #[derive (Clone, Default, Debug)]
@@ -17,6 +17,14 @@ impl ObjectRenderer for CubeRenderer {
     fn get_index_count(&self) -> usize {
         INDICIES.len()
     }
+
+    fn get_vertices(&self) -> &[f32] {
+        &VERTICIES
+    }
+
+    fn get_indices(&self) -> Indices<'_> {
+        Indices::U16(&INDICIES)
+    }
 }
 
 impl HasComponents for CubeRenderer {
@@ -43,7 +51,7 @@ impl Renderable for CubeRenderer {
     fn init(&mut self, gl: &web_sys::WebGl2RenderingContext) {
         unsafe {
             if BUFFERS.is_none() {
-                BUFFERS = Some(Buffers::new(gl, &VERTICIES, &INDICIES, &NORMALS).unwrap());
+                BUFFERS = Some(Buffers::new(gl, &VERTICIES, &INDICIES[..], &NORMALS).unwrap());
             }
         }
     }
@@ -0,0 +1,324 @@
+use crate::log;
+use crate::render::get_engine_block;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlUniformLocation};
+
+/// Active uniform of a program, arrays are named without their `[0]` suffix.
+#[derive(Clone, Debug)]
+pub struct UniformInfo {
+    pub name: String,
+    pub location: WebGlUniformLocation,
+    /// GL type, such as `FLOAT_VEC3`.
+    pub kind: u32,
+    /// Number of elements, 1 unless the uniform is an array.
+    pub size: i32,
+}
+
+/// Active vertex attribute of a program.
+#[derive(Clone, Debug)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub location: u32,
+    pub kind: u32,
+    pub size: i32,
+}
+
+/// Active uniform block of a program, its uniforms are not listed with the others.
+#[derive(Clone, Debug)]
+pub struct UniformBlockInfo {
+    pub name: String,
+    pub index: u32,
+    /// Bytes the program reads from the buffer bound to the block.
+    pub size: usize,
+}
+
+/// Name of the GL types uniforms can have, for warnings.
+pub fn get_type_name(kind: u32) -> &'static str {
+    match kind {
+        WebGl2RenderingContext::FLOAT => "float",
+        WebGl2RenderingContext::FLOAT_VEC2 => "vec2",
+        WebGl2RenderingContext::FLOAT_VEC3 => "vec3",
+        WebGl2RenderingContext::FLOAT_VEC4 => "vec4",
+        WebGl2RenderingContext::FLOAT_MAT3 => "mat3",
+        WebGl2RenderingContext::FLOAT_MAT4 => "mat4",
+        WebGl2RenderingContext::INT => "int",
+        WebGl2RenderingContext::BOOL => "bool",
+        WebGl2RenderingContext::SAMPLER_2D => "sampler2D",
+        WebGl2RenderingContext::SAMPLER_CUBE => "samplerCube",
+        _ => "unknown",
+    }
+}
+
+/// Value `ShaderProgram::set_uniform` can upload, checked against the type of the uniform.
+pub trait UniformValue {
+    fn accepts(&self, kind: u32) -> bool;
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation);
+}
+
+impl UniformValue for f32 {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform1f(Some(location), *self);
+    }
+}
+
+/// Integers, booleans and texture units of samplers.
+impl UniformValue for i32 {
+    fn accepts(&self, kind: u32) -> bool {
+        matches!(
+            kind,
+            WebGl2RenderingContext::INT
+                | WebGl2RenderingContext::BOOL
+                | WebGl2RenderingContext::SAMPLER_2D
+                | WebGl2RenderingContext::SAMPLER_CUBE
+        )
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform1i(Some(location), *self);
+    }
+}
+
+impl UniformValue for [f32; 2] {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT_VEC2
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform2fv_with_f32_array(Some(location), self);
+    }
+}
+
+impl UniformValue for [f32; 3] {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT_VEC3
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform3fv_with_f32_array(Some(location), self);
+    }
+}
+
+impl UniformValue for [f32; 4] {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT_VEC4
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform4fv_with_f32_array(Some(location), self);
+    }
+}
+
+/// Column-major 4x4 matrix, as built by `mat4`.
+impl UniformValue for [f32; 16] {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT_MAT4
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        gl.uniform_matrix4fv_with_f32_array(Some(location), false, self);
+    }
+}
+
+/// Array of `vec3`, elements past its end keep their previous values.
+impl UniformValue for [[f32; 3]] {
+    fn accepts(&self, kind: u32) -> bool {
+        kind == WebGl2RenderingContext::FLOAT_VEC3
+    }
+
+    fn upload(&self, gl: &WebGl2RenderingContext, location: &WebGlUniformLocation) {
+        let values: Vec<f32> = self.iter().flatten().copied().collect();
+
+        gl.uniform3fv_with_f32_array(Some(location), &values);
+    }
+}
+
+/// Linked program with its active uniforms and attributes, introspected once.
+///
+/// Clones share the reflection, so looking uniforms up by name costs no call into GL.
+#[derive(Clone, Debug)]
+pub struct ShaderProgram {
+    program: WebGlProgram,
+    uniforms: Rc<HashMap<String, UniformInfo>>,
+    attributes: Rc<HashMap<String, AttributeInfo>>,
+    blocks: Rc<HashMap<String, UniformBlockInfo>>,
+    /// Warnings already logged, so a warning raised every frame is only logged once.
+    warned: Rc<RefCell<HashSet<String>>>,
+}
+
+fn get_count(gl: &WebGl2RenderingContext, program: &WebGlProgram, parameter: u32) -> u32 {
+    gl.get_program_parameter(program, parameter).as_f64().unwrap_or(0.) as u32
+}
+
+impl ShaderProgram {
+    /// Introspects `program` and binds the blocks the scene fills, such as `Camera`, to their binding point.
+    pub fn new(gl: &WebGl2RenderingContext, program: WebGlProgram) -> Self {
+        let mut uniforms = HashMap::new();
+        let mut attributes = HashMap::new();
+        let mut blocks = HashMap::new();
+
+        for index in 0..get_count(gl, &program, WebGl2RenderingContext::ACTIVE_UNIFORMS) {
+            let info = match gl.get_active_uniform(&program, index) {
+                Some(info) => info,
+                None => continue,
+            };
+
+            let name = info.name();
+
+            // Uniforms of blocks have no location, they are set through their buffer.
+            let location = match gl.get_uniform_location(&program, &name) {
+                Some(location) => location,
+                None => continue,
+            };
+
+            let name = name.trim_end_matches("[0]").to_string();
+
+            uniforms.insert(
+                name.clone(),
+                UniformInfo {
+                    name,
+                    location,
+                    kind: info.type_(),
+                    size: info.size(),
+                },
+            );
+        }
+
+        for index in 0..get_count(gl, &program, WebGl2RenderingContext::ACTIVE_ATTRIBUTES) {
+            let info = match gl.get_active_attrib(&program, index) {
+                Some(info) => info,
+                None => continue,
+            };
+
+            let name = info.name();
+
+            let location = gl.get_attrib_location(&program, &name);
+
+            if location < 0 {
+                continue;
+            }
+
+            attributes.insert(
+                name.clone(),
+                AttributeInfo {
+                    name,
+                    location: location as u32,
+                    kind: info.type_(),
+                    size: info.size(),
+                },
+            );
+        }
+
+        for index in 0..get_count(gl, &program, WebGl2RenderingContext::ACTIVE_UNIFORM_BLOCKS) {
+            let name = match gl.get_active_uniform_block_name(&program, index) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let size = gl
+                .get_active_uniform_block_parameter(&program, index, WebGl2RenderingContext::UNIFORM_BLOCK_DATA_SIZE)
+                .ok()
+                .and_then(|size| size.as_f64())
+                .unwrap_or(0.) as usize;
+
+            blocks.insert(name.clone(), UniformBlockInfo { name, index, size });
+        }
+
+        let program = ShaderProgram {
+            program,
+            uniforms: Rc::new(uniforms),
+            attributes: Rc::new(attributes),
+            blocks: Rc::new(blocks),
+            warned: Rc::default(),
+        };
+
+        for block in program.blocks.values() {
+            if let Some((binding, size)) = get_engine_block(&block.name) {
+                gl.uniform_block_binding(&program.program, block.index, binding);
+
+                if block.size > size {
+                    program.warn(&format!(
+                        "uniform block {} reads {} bytes but the scene writes {}",
+                        block.name, block.size, size
+                    ));
+                }
+            }
+        }
+
+        program
+    }
+
+    pub fn get_program(&self) -> &WebGlProgram {
+        &self.program
+    }
+
+    pub fn get_uniform(&self, name: &str) -> Option<&UniformInfo> {
+        self.uniforms.get(name)
+    }
+
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.uniforms.contains_key(name)
+    }
+
+    /// Active uniforms sorted by name.
+    pub fn get_uniforms(&self) -> Vec<&UniformInfo> {
+        let mut uniforms: Vec<&UniformInfo> = self.uniforms.values().collect();
+
+        uniforms.sort_by(|a, b| a.name.cmp(&b.name));
+
+        uniforms
+    }
+
+    pub fn get_uniform_block(&self, name: &str) -> Option<&UniformBlockInfo> {
+        self.blocks.get(name)
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<&AttributeInfo> {
+        self.attributes.get(name)
+    }
+
+    pub fn get_attribute_location(&self, name: &str) -> Option<u32> {
+        self.attributes.get(name).map(|attribute| attribute.location)
+    }
+
+    /// Active attributes sorted by location.
+    pub fn get_attributes(&self) -> Vec<&AttributeInfo> {
+        let mut attributes: Vec<&AttributeInfo> = self.attributes.values().collect();
+
+        attributes.sort_by_key(|attribute| attribute.location);
+
+        attributes
+    }
+
+    /// Uploads `value` to the uniform `name` of the program in use.
+    ///
+    /// Uniforms the shader does not use are removed by the compiler, so missing ones are skipped.
+    /// A value of the wrong type is skipped with a warning.
+    pub fn set_uniform<T: UniformValue + ?Sized>(&self, gl: &WebGl2RenderingContext, name: &str, value: &T) {
+        let uniform = match self.uniforms.get(name) {
+            Some(uniform) => uniform,
+            None => return,
+        };
+
+        if !value.accepts(uniform.kind) {
+            self.warn(&format!("uniform {} is a {}, the value set has another type", name, get_type_name(uniform.kind)));
+
+            return;
+        }
+
+        value.upload(gl, &uniform.location);
+    }
+
+    /// Logs `message` the first time it is raised for this program.
+    pub fn warn(&self, message: &str) {
+        if self.warned.borrow_mut().insert(message.to_string()) {
+            log!("Shader warning: {}", message);
+        }
+    }
+}
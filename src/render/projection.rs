@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Orthographic {
+    /// Vertical half-height of the view volume, width follows the aspect ratio.
+    SIZE(f32),
+    BOUNDS {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    PERSPECTIVE {
+        fov: f32,
+        near: f32,
+        far: f32,
+    },
+    ORTHOGRAPHIC {
+        extent: Orthographic,
+        near: f32,
+        far: f32,
+    },
+    /// Perspective projection whose near plane is replaced by `clip_plane`,
+    /// given in camera space as `[a, b, c, d]` (e.g. mirrors and portals).
+    OBLIQUE {
+        fov: f32,
+        near: f32,
+        far: f32,
+        clip_plane: [f32; 4],
+    },
+    CUSTOM([f32; 16]),
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::PERSPECTIVE {
+            fov: 30.,
+            near: 0.01,
+            far: 1000.,
+        }
+    }
+}
+
+impl Projection {
+    pub fn get_fov(&self) -> Option<f32> {
+        match self {
+            Projection::PERSPECTIVE { fov, .. } | Projection::OBLIQUE { fov, .. } => Some(*fov),
+            _ => None,
+        }
+    }
+
+    pub fn set_fov(&mut self, value: f32) -> bool {
+        match self {
+            Projection::PERSPECTIVE { fov, .. } | Projection::OBLIQUE { fov, .. } => {
+                let changed = *fov != value;
+
+                *fov = value;
+
+                changed
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get_near(&self) -> Option<f32> {
+        match self {
+            Projection::PERSPECTIVE { near, .. }
+            | Projection::ORTHOGRAPHIC { near, .. }
+            | Projection::OBLIQUE { near, .. } => Some(*near),
+            Projection::CUSTOM(_) => None,
+        }
+    }
+
+    pub fn set_near(&mut self, value: f32) -> bool {
+        match self {
+            Projection::PERSPECTIVE { near, .. }
+            | Projection::ORTHOGRAPHIC { near, .. }
+            | Projection::OBLIQUE { near, .. } => {
+                let changed = *near != value;
+
+                *near = value;
+
+                changed
+            }
+            Projection::CUSTOM(_) => false,
+        }
+    }
+
+    pub fn get_far(&self) -> Option<f32> {
+        match self {
+            Projection::PERSPECTIVE { far, .. }
+            | Projection::ORTHOGRAPHIC { far, .. }
+            | Projection::OBLIQUE { far, .. } => Some(*far),
+            Projection::CUSTOM(_) => None,
+        }
+    }
+
+    pub fn set_far(&mut self, value: f32) -> bool {
+        match self {
+            Projection::PERSPECTIVE { far, .. }
+            | Projection::ORTHOGRAPHIC { far, .. }
+            | Projection::OBLIQUE { far, .. } => {
+                let changed = *far != value;
+
+                *far = value;
+
+                changed
+            }
+            Projection::CUSTOM(_) => false,
+        }
+    }
+
+    pub fn calculate_matrix(&self, aspect: f32) -> [f32; 16] {
+        let mut matrix = mat4::new_identity::<f32>();
+
+        match *self {
+            Projection::PERSPECTIVE { fov, near, far } => {
+                mat4::perspective(&mut matrix, &fov, &aspect, &near, &far);
+            }
+            Projection::ORTHOGRAPHIC { extent, near, far } => {
+                let (left, right, bottom, top) = match extent {
+                    Orthographic::SIZE(size) => (-size * aspect, size * aspect, -size, size),
+                    Orthographic::BOUNDS {
+                        left,
+                        right,
+                        bottom,
+                        top,
+                    } => (left, right, bottom, top),
+                };
+
+                mat4::orthographic(&mut matrix, &top, &right, &bottom, &left, &near, &far);
+            }
+            Projection::OBLIQUE {
+                fov,
+                near,
+                far,
+                clip_plane,
+            } => {
+                mat4::perspective(&mut matrix, &fov, &aspect, &near, &far);
+
+                oblique_near_plane(&mut matrix, clip_plane);
+            }
+            Projection::CUSTOM(custom) => {
+                matrix = custom;
+            }
+        }
+
+        matrix
+    }
+}
+
+// Lengyel, "Oblique View Frustum Depth Projection and Clipping".
+fn oblique_near_plane(matrix: &mut [f32; 16], plane: [f32; 4]) {
+    let q = [
+        (plane[0].signum() + matrix[8]) / matrix[0],
+        (plane[1].signum() + matrix[9]) / matrix[5],
+        -1.,
+        (1. + matrix[10]) / matrix[14],
+    ];
+
+    let dot = plane[0] * q[0] + plane[1] * q[1] + plane[2] * q[2] + plane[3] * q[3];
+
+    if dot == 0. {
+        return;
+    }
+
+    let scale = 2. / dot;
+
+    matrix[2] = plane[0] * scale;
+    matrix[6] = plane[1] * scale;
+    matrix[10] = plane[2] * scale + 1.;
+    matrix[14] = plane[3] * scale;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_matrix(matrix: [f32; 16], expected: [f32; 16]) {
+        for i in 0..16 {
+            assert!((matrix[i] - expected[i]).abs() < 1e-5, "{:?} != {:?}", matrix, expected);
+        }
+    }
+
+    /// Normalized device depth of a camera space point.
+    fn depth(matrix: &[f32; 16], p: [f32; 3]) -> f32 {
+        let z = matrix[2] * p[0] + matrix[6] * p[1] + matrix[10] * p[2] + matrix[14];
+        let w = matrix[3] * p[0] + matrix[7] * p[1] + matrix[11] * p[2] + matrix[15];
+
+        z / w
+    }
+
+    #[test]
+    fn perspective_matrix() {
+        let projection = Projection::PERSPECTIVE {
+            fov: FRAC_PI_2,
+            near: 1.,
+            far: 10.,
+        };
+
+        let expected = [
+            0.5, 0., 0., 0., //
+            0., 1., 0., 0., //
+            0., 0., -11. / 9., -1., //
+            0., 0., -20. / 9., 0.,
+        ];
+
+        assert_matrix(projection.calculate_matrix(2.), expected);
+    }
+
+    #[test]
+    fn orthographic_size_follows_the_aspect() {
+        let projection = Projection::ORTHOGRAPHIC {
+            extent: Orthographic::SIZE(2.),
+            near: 1.,
+            far: 11.,
+        };
+
+        let expected = [
+            1. / 3., 0., 0., 0., //
+            0., 0.5, 0., 0., //
+            0., 0., -0.2, 0., //
+            0., 0., -1.2, 1.,
+        ];
+
+        assert_matrix(projection.calculate_matrix(1.5), expected);
+    }
+
+    #[test]
+    fn orthographic_bounds_ignore_the_aspect() {
+        let projection = Projection::ORTHOGRAPHIC {
+            extent: Orthographic::BOUNDS {
+                left: 0.,
+                right: 4.,
+                bottom: -1.,
+                top: 3.,
+            },
+            near: 0.,
+            far: 2.,
+        };
+
+        let expected = [
+            0.5, 0., 0., 0., //
+            0., 0.5, 0., 0., //
+            0., 0., -1., 0., //
+            -1., -0.5, -1., 1.,
+        ];
+
+        assert_matrix(projection.calculate_matrix(1.), expected);
+        assert_matrix(projection.calculate_matrix(3.), expected);
+    }
+
+    #[test]
+    fn oblique_plane_is_the_near_plane() {
+        // Facing away from the camera, 2 units in front of it and tilted up.
+        let clip_plane = [0., 0.6, -0.8, -2.];
+
+        let matrix = Projection::OBLIQUE {
+            fov: FRAC_PI_2,
+            near: 1.,
+            far: 10.,
+            clip_plane,
+        }
+        .calculate_matrix(1.);
+
+        for &p in [[0., 0., -2.5], [1., 0., -2.5], [0., 1., -1.75], [-2., -1., -3.25]].iter() {
+            let distance = clip_plane[0] * p[0] + clip_plane[1] * p[1] + clip_plane[2] * p[2] + clip_plane[3];
+
+            assert!(distance.abs() < 1e-5);
+            assert!((depth(&matrix, p) + 1.).abs() < 1e-4, "{:?} at {}", p, depth(&matrix, p));
+        }
+
+        // Points past the plane stay in front of the far plane.
+        let z = depth(&matrix, [0., 0., -4.]);
+
+        assert!(z > -1. && z < 1.);
+
+        // Only the depth row changes.
+        let perspective = Projection::PERSPECTIVE {
+            fov: FRAC_PI_2,
+            near: 1.,
+            far: 10.,
+        }
+        .calculate_matrix(1.);
+
+        for &i in [0, 1, 3, 4, 5, 7, 8, 9, 11, 12, 13, 15].iter() {
+            assert_eq!(matrix[i], perspective[i]);
+        }
+    }
+
+    #[test]
+    fn custom_matrix_is_kept() {
+        let custom = [2.; 16];
+
+        assert_eq!(Projection::CUSTOM(custom).calculate_matrix(1.), custom);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::render::{BoundingBox, BoundingSphere};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayIntersection {
+    pub distance: f32,
+    pub normal: [f32; 3],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    origin: [f32; 3],
+    direction: [f32; 3],
+}
+
+impl Ray {
+    pub fn new(origin: [f32; 3], direction: [f32; 3]) -> Self {
+        let mut normalized = [0f32; 3];
+
+        vec3::norm(&mut normalized, &direction);
+
+        Ray {
+            origin,
+            direction: normalized,
+        }
+    }
+
+    pub fn get_origin(&self) -> [f32; 3] {
+        self.origin
+    }
+
+    pub fn get_direction(&self) -> [f32; 3] {
+        self.direction
+    }
+
+    pub fn get_point(&self, distance: f32) -> [f32; 3] {
+        let mut offset = [0f32; 3];
+        let mut point = [0f32; 3];
+
+        vec3::smul(&mut offset, &self.direction, &distance);
+        vec3::add(&mut point, &self.origin, &offset);
+
+        point
+    }
+
+    /// Moves the ray into the space of `matrix`. The direction is not normalized
+    /// again so distances along the ray are kept between both spaces.
+    pub fn transform(&self, matrix: &[f32; 16]) -> Ray {
+        let mut origin = [0f32; 3];
+        let mut direction = [0f32; 3];
+
+        vec3::transform_mat4(&mut origin, &self.origin, matrix);
+        vec3::transform_mat4_rotation(&mut direction, &self.direction, matrix);
+
+        Ray { origin, direction }
+    }
+
+    pub fn intersect_box(&self, aabb: &BoundingBox) -> Option<RayIntersection> {
+        let min = aabb.get_min();
+        let max = aabb.get_max();
+
+        let mut t_min = f32::NEG_INFINITY;
+        let mut t_max = f32::INFINITY;
+        let mut normal = [0f32; 3];
+
+        for i in 0..3 {
+            if self.direction[i] == 0. {
+                if self.origin[i] < min[i] || self.origin[i] > max[i] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let inv = 1. / self.direction[i];
+
+            let mut t0 = (min[i] - self.origin[i]) * inv;
+            let mut t1 = (max[i] - self.origin[i]) * inv;
+
+            let mut sign = -1.;
+
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+
+                sign = 1.;
+            }
+
+            if t0 > t_min {
+                t_min = t0;
+
+                normal = [0f32; 3];
+                normal[i] = sign;
+            }
+
+            t_max = t_max.min(t1);
+
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        if t_max < 0. {
+            return None;
+        }
+
+        if t_min < 0. {
+            // Origin is inside the box.
+            return Some(RayIntersection {
+                distance: 0.,
+                normal: [-self.direction[0], -self.direction[1], -self.direction[2]],
+            });
+        }
+
+        Some(RayIntersection {
+            distance: t_min,
+            normal,
+        })
+    }
+
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<RayIntersection> {
+        let center = sphere.get_center();
+        let radius = sphere.get_radius();
+
+        let mut offset = [0f32; 3];
+
+        vec3::sub(&mut offset, &self.origin, &center);
+
+        let a = vec3::dot(&self.direction, &self.direction);
+        let b = 2. * vec3::dot(&offset, &self.direction);
+        let c = vec3::dot(&offset, &offset) - radius * radius;
+
+        let discriminant = b * b - 4. * a * c;
+
+        if discriminant < 0. || a == 0. {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+
+        let t0 = (-b - root) / (2. * a);
+        let t1 = (-b + root) / (2. * a);
+
+        let distance = if t0 >= 0. {
+            t0
+        } else if t1 >= 0. {
+            0.
+        } else {
+            return None;
+        };
+
+        let point = self.get_point(distance);
+
+        let mut normal = [0f32; 3];
+
+        vec3::sub(&mut offset, &point, &center);
+        vec3::norm(&mut normal, &offset);
+
+        Some(RayIntersection { distance, normal })
+    }
+
+    /// Möller-Trumbore intersection, hits both faces of the triangle.
+    pub fn intersect_triangle(&self, a: &[f32; 3], b: &[f32; 3], c: &[f32; 3]) -> Option<RayIntersection> {
+        let mut edge1 = [0f32; 3];
+        let mut edge2 = [0f32; 3];
+
+        vec3::sub(&mut edge1, b, a);
+        vec3::sub(&mut edge2, c, a);
+
+        let mut p = [0f32; 3];
+
+        vec3::cross(&mut p, &self.direction, &edge2);
+
+        let determinant = vec3::dot(&edge1, &p);
+
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_determinant = 1. / determinant;
+
+        let mut s = [0f32; 3];
+
+        vec3::sub(&mut s, &self.origin, a);
+
+        let u = vec3::dot(&s, &p) * inv_determinant;
+
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let mut q = [0f32; 3];
+
+        vec3::cross(&mut q, &s, &edge1);
+
+        let v = vec3::dot(&self.direction, &q) * inv_determinant;
+
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let distance = vec3::dot(&edge2, &q) * inv_determinant;
+
+        if distance < 0. {
+            return None;
+        }
+
+        let mut face = [0f32; 3];
+        let mut normal = [0f32; 3];
+
+        vec3::cross(&mut face, &edge1, &edge2);
+        vec3::norm(&mut normal, &face);
+
+        Some(RayIntersection { distance, normal })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(value: [f32; 3], expected: [f32; 3]) {
+        for i in 0..3 {
+            assert!((value[i] - expected[i]).abs() < 1e-5, "{:?} != {:?}", value, expected);
+        }
+    }
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new([0., 0., 0.], [1., 1., 1.])
+    }
+
+    #[test]
+    fn boxes_are_hit_on_the_facing_side() {
+        let hit = Ray::new([-5., 0.5, 0.5], [1., 0., 0.]).intersect_box(&unit_box()).unwrap();
+
+        assert_eq!(hit.distance, 5.);
+        assert_eq!(hit.normal, [-1., 0., 0.]);
+
+        let hit = Ray::new([0.5, 3., 0.5], [0., -2., 0.]).intersect_box(&unit_box()).unwrap();
+
+        assert_eq!(hit.distance, 2.);
+        assert_eq!(hit.normal, [0., 1., 0.]);
+
+        let hit = Ray::new([-1., -1., -1.], [1., 1., 1.]).intersect_box(&unit_box()).unwrap();
+
+        assert!((hit.distance - 3f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn boxes_are_missed_beside_and_behind() {
+        assert!(Ray::new([-5., 2., 0.5], [1., 0., 0.]).intersect_box(&unit_box()).is_none());
+        assert!(Ray::new([-5., 0.5, 0.5], [1., 1., 0.]).intersect_box(&unit_box()).is_none());
+        assert!(Ray::new([5., 0.5, 0.5], [1., 0., 0.]).intersect_box(&unit_box()).is_none());
+    }
+
+    #[test]
+    fn rays_parallel_to_a_face_hit_only_within_its_slab() {
+        assert!(Ray::new([-5., 1., 0.5], [1., 0., 0.]).intersect_box(&unit_box()).is_some());
+        assert!(Ray::new([-5., 1.5, 0.5], [1., 0., 0.]).intersect_box(&unit_box()).is_none());
+        assert!(Ray::new([0.5, -0.5, -5.], [0., 0., 1.]).intersect_box(&unit_box()).is_none());
+    }
+
+    #[test]
+    fn rays_starting_inside_a_box_hit_at_their_origin() {
+        let hit = Ray::new([0.5, 0.5, 0.5], [0., 0., 1.]).intersect_box(&unit_box()).unwrap();
+
+        assert_eq!(hit.distance, 0.);
+        assert_eq!(hit.normal, [0., 0., -1.]);
+    }
+
+    #[test]
+    fn transformed_rays_keep_their_distances() {
+        // World box scaled by 2, the ray is moved into its local space by the inverse scale.
+        let mut inverse = mat4::new_identity::<f32>();
+
+        for i in 0..3 {
+            inverse[i * 5] = 0.5;
+        }
+
+        let ray = Ray::new([-4., 1., 1.], [1., 0., 0.]);
+        let hit = ray.transform(&inverse).intersect_box(&unit_box()).unwrap();
+
+        assert_eq!(hit.distance, 4.);
+        assert_near(ray.get_point(hit.distance), [0., 1., 1.]);
+    }
+
+    #[test]
+    fn spheres_are_hit_on_their_surface() {
+        let sphere = BoundingSphere::new([0., 0., 0.], 1.);
+
+        let hit = Ray::new([0., 0., -5.], [0., 0., 1.]).intersect_sphere(&sphere).unwrap();
+
+        assert_eq!(hit.distance, 4.);
+        assert_near(hit.normal, [0., 0., -1.]);
+
+        // Grazing the top of the sphere.
+        let hit = Ray::new([0., 1., -5.], [0., 0., 1.]).intersect_sphere(&sphere).unwrap();
+
+        assert_eq!(hit.distance, 5.);
+        assert_near(hit.normal, [0., 1., 0.]);
+    }
+
+    #[test]
+    fn spheres_are_missed_beside_and_behind() {
+        let sphere = BoundingSphere::new([0., 0., 0.], 1.);
+
+        assert!(Ray::new([0., 2., -5.], [0., 0., 1.]).intersect_sphere(&sphere).is_none());
+        assert!(Ray::new([0., 0., 5.], [0., 0., 1.]).intersect_sphere(&sphere).is_none());
+    }
+
+    #[test]
+    fn rays_starting_inside_a_sphere_hit_at_their_origin() {
+        let sphere = BoundingSphere::new([0., 0., 0.], 2.);
+
+        let hit = Ray::new([0., 1., 0.], [1., 0., 0.]).intersect_sphere(&sphere).unwrap();
+
+        assert_eq!(hit.distance, 0.);
+        assert_near(hit.normal, [0., 1., 0.]);
+    }
+
+    const TRIANGLE: [[f32; 3]; 3] = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+
+    fn triangle(ray: Ray) -> Option<RayIntersection> {
+        ray.intersect_triangle(&TRIANGLE[0], &TRIANGLE[1], &TRIANGLE[2])
+    }
+
+    #[test]
+    fn triangles_are_hit_from_both_sides() {
+        let front = triangle(Ray::new([0.25, 0.25, 1.], [0., 0., -1.])).unwrap();
+
+        assert!((front.distance - 1.).abs() < 1e-5);
+        assert_near(front.normal, [0., 0., 1.]);
+
+        // The normal stays the one of the counter-clockwise face.
+        let back = triangle(Ray::new([0.25, 0.25, -2.], [0., 0., 1.])).unwrap();
+
+        assert!((back.distance - 2.).abs() < 1e-5);
+        assert_near(back.normal, [0., 0., 1.]);
+    }
+
+    #[test]
+    fn triangles_are_missed_outside_their_edges() {
+        assert!(triangle(Ray::new([1., 1., 1.], [0., 0., -1.])).is_none());
+        assert!(triangle(Ray::new([-0.1, 0.5, 1.], [0., 0., -1.])).is_none());
+        assert!(triangle(Ray::new([0.5, -0.1, 1.], [0., 0., -1.])).is_none());
+    }
+
+    #[test]
+    fn triangles_are_missed_by_parallel_rays_and_behind() {
+        assert!(triangle(Ray::new([0.25, 0.25, 1.], [1., 0., 0.])).is_none());
+        assert!(triangle(Ray::new([-1., 0.25, 0.], [1., 0., 0.])).is_none());
+        assert!(triangle(Ray::new([0.25, 0.25, -1.], [0., 0., -1.])).is_none());
+    }
+}
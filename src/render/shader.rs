@@ -1,5 +1,12 @@
+use crate::component::Shader;
+use crate::render::ShaderProgram;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
 use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlShader};
 
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
 pub fn compile_shader(
     context: &WebGl2RenderingContext,
     shader_type: u32,
@@ -50,31 +57,463 @@ pub fn link_program(
     }
 }
 
-pub fn link_program_str(
+/// Compiles and links a program from sources, the error is the info log of the failing step.
+pub fn build_program(
     gl: &WebGl2RenderingContext,
     vertex_shader: &str,
     fragment_shader: &str,
-) -> WebGlProgram {
-    let v_result = compile_shader(gl, WebGl2RenderingContext::VERTEX_SHADER, vertex_shader);
+) -> Result<WebGlProgram, String> {
+    let vertex_shader = compile_shader(gl, WebGl2RenderingContext::VERTEX_SHADER, vertex_shader)
+        .map_err(|log| format!("vertex shader: {}", log))?;
 
-    let vertex_shader = match v_result {
-        Ok(v) => v,
-        Err(s) => panic!("{:?}", s),
+    let fragment_shader = match compile_shader(gl, WebGl2RenderingContext::FRAGMENT_SHADER, fragment_shader) {
+        Ok(fragment_shader) => fragment_shader,
+        Err(log) => {
+            gl.delete_shader(Some(&vertex_shader));
+
+            return Err(format!("fragment shader: {}", log));
+        }
     };
 
-    let f_result = compile_shader(
-        &gl,
-        WebGl2RenderingContext::FRAGMENT_SHADER,
-        fragment_shader,
-    );
+    let program = link_program(gl, &vertex_shader, &fragment_shader).map_err(|log| format!("program: {}", log));
 
-    let fragment_shader = match f_result {
-        Ok(f) => f,
-        Err(s) => panic!("{:?}", s),
-    };
+    // The program keeps its compiled stages, the shader objects are no longer needed.
+    gl.delete_shader(Some(&vertex_shader));
+    gl.delete_shader(Some(&fragment_shader));
+
+    program
+}
 
-    match link_program(&gl, &vertex_shader, &fragment_shader) {
+pub fn link_program_str(
+    gl: &WebGl2RenderingContext,
+    vertex_shader: &str,
+    fragment_shader: &str,
+) -> WebGlProgram {
+    match build_program(gl, vertex_shader, fragment_shader) {
         Ok(p) => p,
         Err(s) => panic!("{:?}", s),
     }
 }
+
+/// Path of `include` seen from the file at `from`, relative to its directory unless it starts with `/`.
+fn resolve_include(from: &str, include: &str) -> String {
+    let mut parts: Vec<&str> = if include.starts_with('/') {
+        vec![]
+    } else {
+        from.split('/').collect()
+    };
+
+    // The file name of `from`.
+    parts.pop();
+
+    for part in include.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+/// Name between the quotes or angle brackets of an `#include` line, `None` for other lines.
+fn parse_include(line: &str) -> Option<Result<&str, String>> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start().strip_prefix("include")?.trim();
+
+    let name = rest
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| rest.strip_prefix('<').and_then(|rest| rest.strip_suffix('>')));
+
+    Some(name.ok_or_else(|| format!("malformed include {}", rest)))
+}
+
+/// Expands `#include` lines, `#define` injection and keywords before shaders are compiled.
+///
+/// Included files come from a virtual file table keyed by path, so sources can be loaded
+/// with `Assets` or embedded with `include_str!`. Defines and keywords are inserted after
+/// the `#version` line, keywords being defined without a value for `#ifdef` blocks.
+#[derive(Default, Clone, Debug)]
+pub struct Preprocessor {
+    files: HashMap<String, String>,
+    defines: BTreeMap<String, String>,
+}
+
+impl Preprocessor {
+    pub fn set_file(&mut self, path: &str, source: &str) {
+        self.files.insert(path.to_string(), source.to_string());
+    }
+
+    pub fn get_file(&self, path: &str) -> Option<&str> {
+        self.files.get(path).map(String::as_str)
+    }
+
+    pub fn get_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.files.keys().cloned().collect();
+
+        paths.sort();
+
+        paths
+    }
+
+    /// Defines `name` as `value` in every processed shader, such as limits shared with Rust.
+    pub fn set_define(&mut self, name: &str, value: impl ToString) {
+        self.defines.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn remove_define(&mut self, name: &str) {
+        self.defines.remove(name);
+    }
+
+    /// Source of the file at `path` with its includes expanded and `keywords` defined.
+    ///
+    /// `#line` directives keep compile errors at the line of the file they come from, files
+    /// being numbered in the order they are first included, starting with 0 for `path`.
+    pub fn process(&self, path: &str, keywords: &[&str]) -> Result<String, String> {
+        let mut expanded = vec![];
+
+        self.expand(path, &mut vec![], &mut vec![], &mut expanded)?;
+
+        let mut header: Vec<String> = vec![];
+        let mut first_line = 1;
+
+        // `#version` has to stay the first line of the shader.
+        if expanded.first().is_some_and(|line| line.trim_start().starts_with("#version")) {
+            header.push(expanded.remove(0));
+            first_line = 2;
+        }
+
+        for (name, value) in self.defines.iter() {
+            header.push(format!("#define {} {}", name, value));
+        }
+
+        for keyword in keywords.iter().collect::<BTreeSet<_>>() {
+            header.push(format!("#define {}", keyword));
+        }
+
+        header.push(format!("#line {} 0", first_line));
+        header.extend(expanded);
+
+        Ok(header.join("\n"))
+    }
+
+    fn expand(
+        &self,
+        path: &str,
+        stack: &mut Vec<String>,
+        sources: &mut Vec<String>,
+        lines: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if stack.iter().any(|included| included == path) {
+            return Err(format!("include cycle {} -> {}", stack.join(" -> "), path));
+        }
+
+        if stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(format!("includes nested deeper than {} at {}", MAX_INCLUDE_DEPTH, path));
+        }
+
+        let source = self.get_file(path).ok_or_else(|| format!("no shader file {}", path))?;
+
+        let source_number = get_source_number(sources, path);
+
+        if !stack.is_empty() {
+            lines.push(format!("#line 1 {}", source_number));
+        }
+
+        stack.push(path.to_string());
+
+        for (index, line) in source.lines().enumerate() {
+            match parse_include(line) {
+                Some(include) => {
+                    let include = include.map_err(|error| format!("{}:{}: {}", path, index + 1, error))?;
+
+                    self.expand(&resolve_include(path, include), stack, sources, lines)
+                        .map_err(|error| format!("{}:{}: {}", path, index + 1, error))?;
+
+                    lines.push(format!("#line {} {}", index + 2, source_number));
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+
+        stack.pop();
+
+        Ok(())
+    }
+}
+
+/// Index of `path` in `sources`, added at the end the first time it is seen.
+fn get_source_number(sources: &mut Vec<String>, path: &str) -> usize {
+    match sources.iter().position(|source| source == path) {
+        Some(index) => index,
+        None => {
+            sources.push(path.to_string());
+
+            sources.len() - 1
+        }
+    }
+}
+
+/// Identifies a variant by its keywords, regardless of their order or repetitions.
+pub fn get_variant_key(keywords: &[&str]) -> String {
+    keywords.iter().collect::<BTreeSet<_>>().into_iter().copied().collect::<Vec<_>>().join(" ")
+}
+
+struct VariantsState {
+    preprocessor: Preprocessor,
+    vertex: String,
+    fragment: String,
+    cache: HashMap<String, Shader>,
+}
+
+/// Programs built from the same vertex and fragment files with different keywords,
+/// such as `TEXTURED`, `SHADOWS` or `INSTANCED`, compiled on first use and cached by key.
+#[derive(Clone)]
+pub struct ShaderVariants {
+    state: Rc<RefCell<VariantsState>>,
+}
+
+impl std::fmt::Debug for ShaderVariants {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+
+        f.debug_struct("ShaderVariants")
+            .field("vertex", &state.vertex)
+            .field("fragment", &state.fragment)
+            .field("variants", &state.cache.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ShaderVariants {
+    /// Variants of the files at `vertex` and `fragment` in the table of `preprocessor`.
+    pub fn new(preprocessor: Preprocessor, vertex: &str, fragment: &str) -> Self {
+        ShaderVariants {
+            state: Rc::new(RefCell::new(VariantsState {
+                preprocessor,
+                vertex: vertex.to_string(),
+                fragment: fragment.to_string(),
+                cache: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Shader of the variant with `keywords`, compiled the first time it is requested.
+    pub fn get(&self, gl: &WebGl2RenderingContext, keywords: &[&str]) -> Result<Shader, String> {
+        let key = get_variant_key(keywords);
+
+        if let Some(shader) = self.state.borrow().cache.get(&key) {
+            return Ok(shader.clone());
+        }
+
+        let (vertex, fragment) = self.process(keywords)?;
+
+        let program = build_program(gl, &vertex, &fragment).map_err(|error| format!("{} ({})", error, key))?;
+
+        let shader = Shader::from(ShaderProgram::new(gl, program));
+
+        self.state.borrow_mut().cache.insert(key, shader.clone());
+
+        Ok(shader)
+    }
+
+    pub fn get_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.state.borrow().cache.keys().cloned().collect();
+
+        keys.sort();
+
+        keys
+    }
+
+    /// Preprocessed vertex and fragment sources of the variant with `keywords`.
+    pub fn process(&self, keywords: &[&str]) -> Result<(String, String), String> {
+        let state = self.state.borrow();
+
+        Ok((
+            state.preprocessor.process(&state.vertex, keywords)?,
+            state.preprocessor.process(&state.fragment, keywords)?,
+        ))
+    }
+
+    pub fn set_file(&self, path: &str, source: &str) {
+        self.state.borrow_mut().preprocessor.set_file(path, source);
+    }
+
+    pub fn get_file(&self, path: &str) -> Option<String> {
+        self.state.borrow().preprocessor.get_file(path).map(str::to_string)
+    }
+
+    /// Paths of every file the variants can include.
+    pub fn get_paths(&self) -> Vec<String> {
+        self.state.borrow().preprocessor.get_paths()
+    }
+
+    /// Recompiles every cached variant in place, variants that fail keep their current program.
+    pub fn reload(&self, gl: &WebGl2RenderingContext) -> Result<(), String> {
+        let mut errors = vec![];
+
+        for key in self.get_keys() {
+            let keywords: Vec<&str> = key.split_whitespace().collect();
+
+            let shader = self.state.borrow().cache[&key].clone();
+
+            let reloaded = self
+                .process(&keywords)
+                .and_then(|(vertex, fragment)| shader.reload(gl, &vertex, &fragment));
+
+            if let Err(error) = reloaded {
+                errors.push(format!("{} ({})", error, key));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocessor(files: &[(&str, &str)]) -> Preprocessor {
+        let mut preprocessor = Preprocessor::default();
+
+        for (path, source) in files {
+            preprocessor.set_file(path, source);
+        }
+
+        preprocessor
+    }
+
+    #[test]
+    fn includes_resolve_relative_to_the_including_file() {
+        assert_eq!(resolve_include("shaders/main.glsl", "common.glsl"), "shaders/common.glsl");
+        assert_eq!(resolve_include("shaders/main.glsl", "../lib/light.glsl"), "lib/light.glsl");
+        assert_eq!(resolve_include("shaders/main.glsl", "./a/./b.glsl"), "shaders/a/b.glsl");
+        assert_eq!(resolve_include("shaders/main.glsl", "/lib/light.glsl"), "lib/light.glsl");
+    }
+
+    #[test]
+    fn include_lines_are_parsed() {
+        assert_eq!(parse_include("#include \"light.glsl\""), Some(Ok("light.glsl")));
+        assert_eq!(parse_include("  # include <light.glsl>  "), Some(Ok("light.glsl")));
+        assert_eq!(parse_include("#define LIGHTS 4"), None);
+        assert_eq!(parse_include("vec3 include;"), None);
+        assert!(matches!(parse_include("#include light.glsl"), Some(Err(_))));
+    }
+
+    #[test]
+    fn includes_are_expanded_with_line_directives() {
+        let preprocessor = preprocessor(&[
+            ("main.glsl", "#version 300 es\n#include \"lib/a.glsl\"\nvoid main() {}"),
+            ("lib/a.glsl", "float a;\n#include \"b.glsl\"\nfloat c;"),
+            ("lib/b.glsl", "float b;"),
+        ]);
+
+        let source = preprocessor.process("main.glsl", &[]).unwrap();
+
+        assert_eq!(
+            source.lines().collect::<Vec<_>>(),
+            vec![
+                "#version 300 es",
+                "#line 2 0",
+                "#line 1 1",
+                "float a;",
+                "#line 1 2",
+                "float b;",
+                "#line 3 1",
+                "float c;",
+                "#line 3 0",
+                "void main() {}",
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_includes_keep_their_source_number() {
+        let preprocessor = preprocessor(&[
+            ("main.glsl", "#include \"a.glsl\"\n#include \"a.glsl\""),
+            ("a.glsl", "float a;"),
+        ]);
+
+        let source = preprocessor.process("main.glsl", &[]).unwrap();
+
+        assert_eq!(source.matches("#line 1 1").count(), 2);
+        assert!(source.starts_with("#line 1 0\n"));
+    }
+
+    #[test]
+    fn defines_and_keywords_follow_the_version() {
+        let mut preprocessor = preprocessor(&[("main.glsl", "#version 300 es\nvoid main() {}")]);
+
+        preprocessor.set_define("MAX_LIGHTS", 4);
+        preprocessor.set_define("REMOVED", 1);
+        preprocessor.remove_define("REMOVED");
+
+        let source = preprocessor.process("main.glsl", &["TEXTURED", "SHADOWS", "TEXTURED"]).unwrap();
+
+        assert_eq!(
+            source.lines().collect::<Vec<_>>(),
+            vec![
+                "#version 300 es",
+                "#define MAX_LIGHTS 4",
+                "#define SHADOWS",
+                "#define TEXTURED",
+                "#line 2 0",
+                "void main() {}",
+            ]
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_rejected() {
+        let preprocessor = preprocessor(&[
+            ("a.glsl", "#include \"b.glsl\""),
+            ("b.glsl", "float b;\n#include \"a.glsl\""),
+        ]);
+
+        let error = preprocessor.process("a.glsl", &[]).unwrap_err();
+
+        assert!(error.contains("include cycle a.glsl -> b.glsl -> a.glsl"), "{}", error);
+        assert!(error.starts_with("a.glsl:1: b.glsl:2: "), "{}", error);
+    }
+
+    #[test]
+    fn deep_includes_are_rejected() {
+        let files: Vec<(String, String)> = (0..=MAX_INCLUDE_DEPTH)
+            .map(|i| (format!("{}.glsl", i), format!("#include \"{}.glsl\"", i + 1)))
+            .collect();
+
+        let mut preprocessor = Preprocessor::default();
+
+        for (path, source) in files.iter() {
+            preprocessor.set_file(path, source);
+        }
+
+        let error = preprocessor.process("0.glsl", &[]).unwrap_err();
+
+        assert!(error.contains("nested deeper than"), "{}", error);
+    }
+
+    #[test]
+    fn missing_includes_name_the_file() {
+        let preprocessor = preprocessor(&[("main.glsl", "float a;\n#include \"missing.glsl\"")]);
+
+        let error = preprocessor.process("main.glsl", &[]).unwrap_err();
+
+        assert_eq!(error, "main.glsl:2: no shader file missing.glsl");
+    }
+
+    #[test]
+    fn variant_keys_ignore_order_and_repetitions() {
+        assert_eq!(get_variant_key(&["TEXTURED", "SHADOWS"]), "SHADOWS TEXTURED");
+        assert_eq!(get_variant_key(&["SHADOWS", "TEXTURED", "SHADOWS"]), "SHADOWS TEXTURED");
+        assert_eq!(get_variant_key(&[]), "");
+    }
+}
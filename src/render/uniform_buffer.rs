@@ -0,0 +1,345 @@
+use crate::render::{CameraRenderer, LightData};
+use web_sys::{WebGl2RenderingContext, WebGlBuffer};
+
+/// Binding points of the uniform blocks filled by the scene.
+pub const FRAME_BINDING: u32 = 0;
+pub const CAMERA_BINDING: u32 = 1;
+
+/// Writes values with the alignment and padding of `layout(std140)` uniform blocks.
+///
+/// Every `push` returns the byte offset the value was written at.
+#[derive(Default, Clone, Debug)]
+pub struct Std140 {
+    bytes: Vec<u8>,
+}
+
+impl Std140 {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn align(&mut self, alignment: usize) -> usize {
+        let offset = self.bytes.len().div_ceil(alignment) * alignment;
+
+        self.bytes.resize(offset, 0);
+
+        offset
+    }
+
+    fn push_floats(&mut self, alignment: usize, values: &[f32]) -> usize {
+        let offset = self.align(alignment);
+
+        for value in values {
+            self.bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        offset
+    }
+
+    pub fn push_f32(&mut self, value: f32) -> usize {
+        self.push_floats(4, &[value])
+    }
+
+    pub fn push_i32(&mut self, value: i32) -> usize {
+        let offset = self.align(4);
+
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+
+        offset
+    }
+
+    pub fn push_vec2(&mut self, value: &[f32; 2]) -> usize {
+        self.push_floats(8, value)
+    }
+
+    /// Aligned like a `vec4`, a following scalar fills its last 4 bytes.
+    pub fn push_vec3(&mut self, value: &[f32; 3]) -> usize {
+        self.push_floats(16, value)
+    }
+
+    pub fn push_vec4(&mut self, value: &[f32; 4]) -> usize {
+        self.push_floats(16, value)
+    }
+
+    /// Column-major matrix, stored as 4 `vec4` columns.
+    pub fn push_mat4(&mut self, value: &[f32; 16]) -> usize {
+        self.push_floats(16, value)
+    }
+
+    /// Every element of an array is padded to 16 bytes, as is the end of the array.
+    pub fn push_f32_array(&mut self, values: &[f32]) -> usize {
+        let offset = self.align(16);
+
+        for value in values {
+            self.push_floats(16, &[*value]);
+        }
+
+        self.align(16);
+
+        offset
+    }
+
+    pub fn push_vec3_array(&mut self, values: &[[f32; 3]]) -> usize {
+        let offset = self.align(16);
+
+        for value in values {
+            self.push_floats(16, value);
+        }
+
+        self.align(16);
+
+        offset
+    }
+
+    /// Bytes of the block, its size rounded up to 16 like a structure.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.align(16);
+
+        self.bytes
+    }
+}
+
+/// Content of the `Frame` block, shared by every camera of a frame.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct FrameUniforms {
+    /// Seconds since the scene started.
+    pub time: f32,
+    pub lights: LightData,
+}
+
+impl FrameUniforms {
+    pub fn to_std140(&self) -> Vec<u8> {
+        let mut std140 = Std140::default();
+
+        std140.push_vec3(&self.lights.ambient);
+        std140.push_f32(self.time);
+        std140.push_vec3_array(&self.lights.directions);
+        std140.push_vec3_array(&self.lights.colors);
+        std140.push_i32(self.lights.count as i32);
+
+        std140.finish()
+    }
+}
+
+/// Content of the `Camera` block, written before each camera pass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraUniforms {
+    pub projection: [f32; 16],
+    pub view: [f32; 16],
+    pub position: [f32; 3],
+}
+
+impl Default for CameraUniforms {
+    fn default() -> Self {
+        CameraUniforms {
+            projection: mat4::new_identity(),
+            view: mat4::new_identity(),
+            position: [0.; 3],
+        }
+    }
+}
+
+impl CameraUniforms {
+    pub fn new(camera: &mut CameraRenderer) -> Self {
+        CameraUniforms {
+            projection: camera.get_projection_matrix(),
+            view: camera.get_camera_matrix(),
+            position: camera.get_position(),
+        }
+    }
+
+    pub fn to_std140(&self) -> Vec<u8> {
+        let mut std140 = Std140::default();
+
+        std140.push_mat4(&self.projection);
+        std140.push_mat4(&self.view);
+        std140.push_vec3(&self.position);
+
+        std140.finish()
+    }
+}
+
+/// Binding point and std140 size of the blocks filled by the scene, by block name.
+pub fn get_engine_block(name: &str) -> Option<(u32, usize)> {
+    match name {
+        "Frame" => Some((FRAME_BINDING, FrameUniforms::default().to_std140().len())),
+        "Camera" => Some((CAMERA_BINDING, CameraUniforms::default().to_std140().len())),
+        _ => None,
+    }
+}
+
+/// Buffer backing a uniform block, bound to its binding point every time it is uploaded.
+#[derive(Clone, Debug)]
+pub struct UniformBuffer {
+    buffer: Option<WebGlBuffer>,
+    binding: u32,
+    size: usize,
+}
+
+impl UniformBuffer {
+    pub fn new(gl: &WebGl2RenderingContext, binding: u32) -> Result<Self, &'static str> {
+        Ok(UniformBuffer {
+            buffer: Some(gl.create_buffer().ok_or("failed to create uniform buffer")?),
+            binding,
+            size: 0,
+        })
+    }
+
+    pub fn get_binding(&self) -> u32 {
+        self.binding
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Replaces the content of the buffer, reallocating it only when the size changes.
+    pub fn upload(&mut self, gl: &WebGl2RenderingContext, bytes: &[u8]) {
+        gl.bind_buffer(WebGl2RenderingContext::UNIFORM_BUFFER, self.buffer.as_ref());
+
+        if bytes.len() == self.size {
+            gl.buffer_sub_data_with_i32_and_u8_array(WebGl2RenderingContext::UNIFORM_BUFFER, 0, bytes);
+        } else {
+            gl.buffer_data_with_u8_array(WebGl2RenderingContext::UNIFORM_BUFFER, bytes, WebGl2RenderingContext::DYNAMIC_DRAW);
+
+            self.size = bytes.len();
+        }
+
+        gl.bind_buffer_base(WebGl2RenderingContext::UNIFORM_BUFFER, self.binding, self.buffer.as_ref());
+    }
+
+    pub fn delete(&mut self, gl: &WebGl2RenderingContext) {
+        gl.delete_buffer(self.buffer.take().as_ref());
+
+        self.size = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::MAX_LIGHTS;
+
+    #[test]
+    fn scalars_are_packed() {
+        let mut std140 = Std140::default();
+
+        assert_eq!(std140.push_f32(1.), 0);
+        assert_eq!(std140.push_i32(2), 4);
+        assert_eq!(std140.push_f32(3.), 8);
+        assert_eq!(std140.len(), 12);
+    }
+
+    #[test]
+    fn vec2_aligns_to_8() {
+        let mut std140 = Std140::default();
+
+        std140.push_f32(1.);
+
+        assert_eq!(std140.push_vec2(&[2., 3.]), 8);
+        assert_eq!(std140.len(), 16);
+    }
+
+    #[test]
+    fn vec3_aligns_to_16_and_shares_its_tail() {
+        let mut std140 = Std140::default();
+
+        std140.push_f32(1.);
+
+        assert_eq!(std140.push_vec3(&[2., 3., 4.]), 16);
+        assert_eq!(std140.push_f32(5.), 28);
+        assert_eq!(std140.push_vec3(&[6., 7., 8.]), 32);
+    }
+
+    #[test]
+    fn vec4_and_mat4_align_to_16() {
+        let mut std140 = Std140::default();
+
+        std140.push_f32(1.);
+
+        assert_eq!(std140.push_vec4(&[2., 3., 4., 5.]), 16);
+        assert_eq!(std140.push_mat4(&[0.; 16]), 32);
+        assert_eq!(std140.len(), 96);
+    }
+
+    #[test]
+    fn array_elements_are_padded_to_16() {
+        let mut std140 = Std140::default();
+
+        std140.push_f32(1.);
+
+        assert_eq!(std140.push_f32_array(&[2., 3., 4.]), 16);
+        assert_eq!(std140.push_f32(5.), 64);
+
+        let mut std140 = Std140::default();
+
+        assert_eq!(std140.push_vec3_array(&[[1.; 3], [2.; 3]]), 0);
+        // The end of the array is padded, a scalar cannot use the tail of the last element.
+        assert_eq!(std140.push_f32(3.), 32);
+    }
+
+    #[test]
+    fn finish_rounds_size_to_16() {
+        let mut std140 = Std140::default();
+
+        std140.push_vec3(&[1., 2., 3.]);
+        std140.push_f32(4.);
+        std140.push_f32(5.);
+
+        assert_eq!(std140.finish().len(), 32);
+        assert!(Std140::default().finish().is_empty());
+    }
+
+    #[test]
+    fn values_are_little_endian() {
+        let mut std140 = Std140::default();
+
+        std140.push_f32(1.);
+        std140.push_i32(-2);
+
+        let bytes = std140.finish();
+
+        assert_eq!(&bytes[0..4], &1f32.to_le_bytes());
+        assert_eq!(&bytes[4..8], &(-2i32).to_le_bytes());
+        assert!(bytes[8..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn frame_block_matches_glsl_layout() {
+        let frame = FrameUniforms {
+            time: 2.5,
+            lights: LightData {
+                ambient: [0.1, 0.2, 0.3],
+                count: 1,
+                ..LightData::default()
+            },
+        };
+
+        let bytes = frame.to_std140();
+        let read = |offset: usize| f32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+
+        // u_ambient, u_time, u_light_directions, u_light_colors, u_light_count.
+        assert_eq!(read(8), 0.3);
+        assert_eq!(read(12), 2.5);
+        assert_eq!(&bytes[16 + 32 * MAX_LIGHTS..16 + 32 * MAX_LIGHTS + 4], &1i32.to_le_bytes());
+        assert_eq!(bytes.len(), 32 + 32 * MAX_LIGHTS);
+    }
+
+    #[test]
+    fn camera_block_matches_glsl_layout() {
+        let camera = CameraUniforms {
+            position: [1., 2., 3.],
+            ..CameraUniforms::default()
+        };
+
+        let bytes = camera.to_std140();
+
+        assert_eq!(&bytes[128..132], &1f32.to_le_bytes());
+        assert_eq!(bytes.len(), 144);
+        assert_eq!(get_engine_block("Camera"), Some((CAMERA_BINDING, 144)));
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Camera viewport as `[x, y, width, height]`, with the origin at the bottom left
+/// of the canvas like `gl.viewport`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Viewport {
+    FRACTION([f32; 4]),
+    PIXELS([i32; 4]),
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::FRACTION([0., 0., 1., 1.])
+    }
+}
+
+impl Viewport {
+    pub fn to_pixels(&self, max_width: u32, max_height: u32) -> [i32; 4] {
+        match *self {
+            Viewport::FRACTION(v) => {
+                let width = max_width as f32;
+                let height = max_height as f32;
+
+                [
+                    (v[0] * width).round() as i32,
+                    (v[1] * height).round() as i32,
+                    (v[2] * width).round() as i32,
+                    (v[3] * height).round() as i32,
+                ]
+            }
+            Viewport::PIXELS(v) => v,
+        }
+    }
+}
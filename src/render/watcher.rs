@@ -0,0 +1,412 @@
+use crate::assets::AssetSource;
+use crate::component::{Component, Shader};
+use crate::log;
+use crate::render::ShaderVariants;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use web_sys::WebGl2RenderingContext;
+
+pub const DEFAULT_POLL_INTERVAL: f32 = 1000.;
+
+/// Relinks watched programs from the last content read for every path.
+type Reload = Box<dyn Fn(&WebGl2RenderingContext, &HashMap<String, String>) -> Result<(), String>>;
+
+struct Watched {
+    paths: Vec<String>,
+    reload: Reload,
+    changed: bool,
+}
+
+struct WatcherState {
+    source: Rc<dyn AssetSource>,
+    interval: f32,
+    elapsed: f32,
+    /// Reads of the current poll that did not complete yet.
+    pending: usize,
+    /// Last content read for every watched path.
+    contents: HashMap<String, String>,
+    watched: Vec<Watched>,
+    error: Option<String>,
+}
+
+/// Polls shader sources during development and relinks the programs of changed ones in place.
+///
+/// Every clone of a watched `Shader` sees the new program, so the scene keeps its state.
+/// When compiling or linking fails the previous program stays in use and the log is printed.
+#[derive(Clone)]
+pub struct ShaderWatcher {
+    state: Rc<RefCell<WatcherState>>,
+}
+
+impl Default for ShaderWatcher {
+    fn default() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let source = crate::assets::FetchSource::default().set_revalidate(true);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let source = crate::assets::DirectorySource::new(".");
+
+        ShaderWatcher::new(source)
+    }
+}
+
+impl fmt::Debug for ShaderWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+
+        f.debug_struct("ShaderWatcher")
+            .field("source", &state.source)
+            .field("interval", &state.interval)
+            .field("watched", &state.watched.len())
+            .finish()
+    }
+}
+
+impl ShaderWatcher {
+    pub fn new(source: impl AssetSource + 'static) -> Self {
+        ShaderWatcher {
+            state: Rc::new(RefCell::new(WatcherState {
+                source: Rc::new(source),
+                interval: DEFAULT_POLL_INTERVAL,
+                elapsed: 0.,
+                pending: 0,
+                contents: HashMap::new(),
+                watched: vec![],
+                error: None,
+            })),
+        }
+    }
+
+    /// Milliseconds between two polls of the sources.
+    pub fn set_interval(&self, interval: f32) -> Result<(), &'static str> {
+        if interval <= 0. {
+            return Err("poll interval must be positive");
+        }
+
+        self.state.borrow_mut().interval = interval;
+
+        Ok(())
+    }
+
+    pub fn get_interval(&self) -> f32 {
+        self.state.borrow().interval
+    }
+
+    /// Relinks `shader` whenever the file at `vertex` or `fragment` changes in the source.
+    pub fn watch(&self, shader: &Shader, vertex: &str, fragment: &str) {
+        let shader = shader.clone();
+        let (vertex, fragment) = (vertex.to_string(), fragment.to_string());
+
+        self.state.borrow_mut().watched.push(Watched {
+            paths: vec![vertex.clone(), fragment.clone()],
+            reload: Box::new(move |gl, contents| {
+                let text = |path: &String| contents.get(path).cloned().unwrap_or_default();
+
+                shader.reload(gl, &text(&vertex), &text(&fragment))
+            }),
+            changed: false,
+        });
+    }
+
+    /// Recompiles the cached variants whenever a file of their preprocessor table changes.
+    ///
+    /// The table is what the variants are built from, so edits made before the first poll are relinked too.
+    pub fn watch_variants(&self, variants: &ShaderVariants) {
+        let paths = variants.get_paths();
+
+        let mut state = self.state.borrow_mut();
+
+        for path in paths.iter() {
+            if let Some(source) = variants.get_file(path) {
+                state.contents.entry(path.clone()).or_insert(source);
+            }
+        }
+
+        let variants = variants.clone();
+
+        state.watched.push(Watched {
+            paths: paths.clone(),
+            reload: Box::new(move |gl, contents| {
+                for path in paths.iter() {
+                    if let Some(source) = contents.get(path) {
+                        variants.set_file(path, source);
+                    }
+                }
+
+                variants.reload(gl)
+            }),
+            changed: false,
+        });
+    }
+
+    /// Whether a change was read but not relinked yet.
+    pub fn has_changes(&self) -> bool {
+        self.state.borrow().watched.iter().any(|watched| watched.changed)
+    }
+
+    /// Log of the last failed reload, cleared once a reload succeeds.
+    pub fn get_error(&self) -> Option<String> {
+        self.state.borrow().error.clone()
+    }
+
+    /// Reads every watched path again once the interval elapsed and the previous poll completed.
+    pub fn poll(&self, dt: f32) {
+        let (source, paths) = {
+            let mut state = self.state.borrow_mut();
+
+            state.elapsed += dt;
+
+            if state.elapsed < state.interval || state.pending > 0 {
+                return;
+            }
+
+            state.elapsed = 0.;
+
+            let mut paths: Vec<String> = state
+                .watched
+                .iter()
+                .flat_map(|watched| watched.paths.iter().cloned())
+                .collect();
+
+            paths.sort();
+            paths.dedup();
+
+            state.pending = paths.len();
+
+            (state.source.clone(), paths)
+        };
+
+        for path in paths {
+            let watcher = self.clone();
+            let read = path.clone();
+
+            // The state is released, memory and directory sources call back before `read` returns.
+            source.read(&path, Box::new(move |bytes| watcher.receive(read, bytes)));
+        }
+    }
+
+    fn receive(&self, path: String, bytes: Result<Vec<u8>, String>) {
+        let mut state = self.state.borrow_mut();
+
+        state.pending -= 1;
+
+        // A file can be missing for a moment while an editor saves it, the next poll reads it again.
+        let text = match bytes {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(_) => return,
+        };
+
+        let changed = match state.contents.insert(path.clone(), text.clone()) {
+            Some(previous) => previous != text,
+            // Without a seeded content, the first read is what the shader was built from.
+            None => false,
+        };
+
+        if changed {
+            for watched in state.watched.iter_mut() {
+                if watched.paths.contains(&path) {
+                    watched.changed = true;
+                }
+            }
+        }
+    }
+
+    /// Relinks the shaders whose sources changed since the last reload.
+    pub fn reload(&self, gl: &WebGl2RenderingContext) {
+        let mut state = self.state.borrow_mut();
+
+        let WatcherState { contents, watched, error, .. } = &mut *state;
+
+        for watched in watched.iter_mut().filter(|watched| watched.changed) {
+            watched.changed = false;
+
+            let paths = watched.paths.join(", ");
+
+            match (watched.reload)(gl, contents) {
+                Ok(()) => {
+                    log!("Reloaded {}", paths);
+
+                    *error = None;
+                }
+                Err(log) => {
+                    let log = format!("{}: {}", paths, log);
+
+                    log!("Failed to reload shader, keeping the previous program. {}", log);
+
+                    *error = Some(log);
+                }
+            }
+        }
+    }
+}
+
+impl Component for ShaderWatcher {
+    fn update(&mut self, dt: f32) {
+        self.poll(dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::MemorySource;
+    use crate::render::Preprocessor;
+    use wasm_bindgen::{JsCast, JsValue};
+
+    /// Reloads in these tests do not touch the context, a null one stands in for it natively.
+    fn null_context() -> WebGl2RenderingContext {
+        JsValue::NULL.unchecked_into()
+    }
+
+    /// Watches `paths` with a reload recording the contents it is given, or failing with `error`.
+    fn watch(watcher: &ShaderWatcher, paths: &[&str], error: Rc<RefCell<Option<String>>>) -> Rc<RefCell<Vec<String>>> {
+        let reloads = Rc::new(RefCell::new(vec![]));
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+
+        {
+            let reloads = reloads.clone();
+            let paths = paths.clone();
+
+            watcher.state.borrow_mut().watched.push(Watched {
+                paths: paths.clone(),
+                reload: Box::new(move |_gl, contents| {
+                    if let Some(error) = error.borrow().clone() {
+                        return Err(error);
+                    }
+
+                    let text: Vec<String> = paths.iter().map(|path| contents[path].clone()).collect();
+
+                    reloads.borrow_mut().push(text.join("+"));
+
+                    Ok(())
+                }),
+                changed: false,
+            });
+        }
+
+        reloads
+    }
+
+    #[test]
+    fn changes_are_relinked_once() {
+        let source = MemorySource::default();
+
+        source.set("vert.glsl", "v1");
+        source.set("frag.glsl", "f1");
+
+        let watcher = ShaderWatcher::new(source.clone());
+        let reloads = watch(&watcher, &["vert.glsl", "frag.glsl"], Rc::default());
+
+        // The first read is what the shader was built from.
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+
+        assert!(!watcher.has_changes());
+
+        source.set("frag.glsl", "f2");
+
+        watcher.poll(DEFAULT_POLL_INTERVAL / 2.);
+
+        assert!(!watcher.has_changes());
+
+        watcher.poll(DEFAULT_POLL_INTERVAL / 2.);
+
+        assert!(watcher.has_changes());
+
+        watcher.reload(&null_context());
+        watcher.reload(&null_context());
+
+        assert_eq!(*reloads.borrow(), vec!["v1+f2"]);
+        assert!(!watcher.has_changes());
+    }
+
+    #[test]
+    fn failed_reload_is_reported_until_a_reload_succeeds() {
+        let source = MemorySource::default();
+
+        source.set("shader.glsl", "a");
+
+        let watcher = ShaderWatcher::new(source.clone());
+        let error = Rc::new(RefCell::new(Some("syntax error".to_string())));
+        let reloads = watch(&watcher, &["shader.glsl"], error.clone());
+
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+
+        source.set("shader.glsl", "b");
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+        watcher.reload(&null_context());
+
+        assert_eq!(watcher.get_error(), Some("shader.glsl: syntax error".to_string()));
+        assert!(reloads.borrow().is_empty());
+
+        *error.borrow_mut() = None;
+
+        source.set("shader.glsl", "c");
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+        watcher.reload(&null_context());
+
+        assert_eq!(watcher.get_error(), None);
+        assert_eq!(*reloads.borrow(), vec!["c"]);
+    }
+
+    #[test]
+    fn missing_file_is_read_again_next_poll() {
+        let source = MemorySource::default();
+
+        source.set("shader.glsl", "a");
+
+        let watcher = ShaderWatcher::new(source.clone());
+        let reloads = watch(&watcher, &["shader.glsl"], Rc::default());
+
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+
+        // An editor saving the file can remove it for a moment.
+        source.remove("shader.glsl");
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+
+        assert!(!watcher.has_changes());
+
+        source.set("shader.glsl", "b");
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+        watcher.reload(&null_context());
+
+        assert_eq!(*reloads.borrow(), vec!["b"]);
+    }
+
+    #[test]
+    fn variants_relink_edits_made_before_the_first_poll() {
+        let mut preprocessor = Preprocessor::default();
+
+        preprocessor.set_file("shader.vert", "#include \"common.glsl\"");
+        preprocessor.set_file("common.glsl", "old");
+
+        let variants = ShaderVariants::new(preprocessor, "shader.vert", "shader.vert");
+        let source = MemorySource::default();
+
+        // Saved while the assets were loading.
+        source.set("shader.vert", "#include \"common.glsl\"");
+        source.set("common.glsl", "new");
+
+        let watcher = ShaderWatcher::new(source);
+
+        watcher.watch_variants(&variants);
+        watcher.poll(DEFAULT_POLL_INTERVAL);
+
+        assert!(watcher.has_changes());
+
+        watcher.reload(&null_context());
+
+        assert_eq!(variants.get_file("common.glsl"), Some("new".to_string()));
+        assert_eq!(watcher.get_error(), None);
+    }
+
+    #[test]
+    fn interval_must_be_positive() {
+        let watcher = ShaderWatcher::new(MemorySource::default());
+
+        assert!(watcher.set_interval(0.).is_err());
+        assert!(watcher.set_interval(250.).is_ok());
+        assert_eq!(watcher.get_interval(), 250.);
+    }
+}
@@ -1,4 +1,5 @@
 const CopyWebpackPlugin = require("copy-webpack-plugin");
+const express = require('express');
 const path = require('path');
 
 module.exports = {
@@ -9,9 +10,13 @@ module.exports = {
     },
     mode: "development",
     plugins: [
-        new CopyWebpackPlugin(['index.html'])
+        new CopyWebpackPlugin(['index.html', { from: '../resources', to: 'resources' }])
     ],
     devServer: {
+        // Serve resources from disk rather than the copies, so edited shaders can be reloaded in place.
+        before(app) {
+            app.use('/resources', express.static(path.resolve(__dirname, '../resources')));
+        },
         open: true,
         port: 8080,
         host: '0.0.0.0'